@@ -0,0 +1,68 @@
+//! Definite assignment analysis finds all variables which are definitely
+//! assigned at a program point.
+//!
+//! This implements a slight variation, where we find the variables which are
+//! possibly unassigned. It is a forwards problem with the following functions
+//!
+//! ```plain
+//! trans(b) = union(gen(b), in(b) - kill(b))
+//! join = union
+//! ```
+//!
+//! where `gen(b)` gives the variables which are declared, and `kill(b)` gives
+//! the variables which are defined.
+
+use super::DefUse;
+use crate::{Analyzer, Fact, Graph, HashMap, HashSet, NodeInfo};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Unassigned<V: Eq + std::hash::Hash> {
+    pub uninit: HashSet<V>,
+}
+
+impl<V: Eq + std::hash::Hash> Unassigned<V> {
+    pub fn new(uninit: HashSet<V>) -> Self {
+        Self { uninit }
+    }
+}
+
+impl<V: Clone + Eq + std::hash::Hash> Fact for Unassigned<V> {}
+
+/// ```plain
+/// trans(b) = union(gen(b), in(b) - kill(b))
+/// ```
+pub fn trans<N: DefUse>(node: &N, mut fact: Unassigned<N::Var>) -> Unassigned<N::Var> {
+    for stmt in 0..node.stmt_count() {
+        fact.uninit.extend(node.decls(stmt).iter().copied());
+
+        for var in node.defs(stmt) {
+            fact.uninit.remove(var);
+        }
+    }
+
+    fact
+}
+
+/// ```plain
+/// join = union
+/// ```
+pub fn join<V: Copy + Eq + std::hash::Hash>(facts: Vec<Unassigned<V>>) -> Unassigned<V> {
+    let mut uninit = HashSet::default();
+
+    for fact in facts {
+        uninit.extend(fact.uninit);
+    }
+
+    Unassigned { uninit }
+}
+
+/// Find the possibly unassigned variables for every node in `graph`
+pub fn solve<N, G>(graph: &G) -> HashMap<N::NodeId, NodeInfo<Unassigned<N::Var>>>
+where
+    N: DefUse,
+    N::NodeId: std::fmt::Debug,
+    G: Graph<N>,
+{
+    let top = Unassigned::new(HashSet::default());
+    Analyzer::new_forward(top, trans, join).solve(graph)
+}
@@ -0,0 +1,55 @@
+//! Available expressions analysis finds all expressions which have been
+//! evaluated on every path to a given point, and whose operands haven't been
+//! assigned since.
+//!
+//! Available expressions is a forwards must problem with these functions
+//!
+//! ```plain
+//! trans(b) = union(gen(b), in(b) - kill(b))
+//! join = intersection
+//! ```
+//!
+//! where `gen(b)` gives the expressions evaluated in `b` whose operands aren't
+//! assigned later in `b`, and `kill(b)` gives the expressions with an operand
+//! assigned in `b`. Nothing is available on entry to the graph.
+
+use super::{kill, Expressions};
+use crate::lattice::{join_all, Must, PowerSet};
+use crate::{Analyzer, Graph, HashMap, NodeInfo};
+
+/// ```plain
+/// trans(b) = union(gen(b), in(b) - kill(b))
+/// ```
+pub fn trans<N: Expressions>(
+    node: &N,
+    mut fact: PowerSet<N::Expr, Must>,
+) -> PowerSet<N::Expr, Must> {
+    for stmt in 0..node.stmt_count() {
+        for expr in node.exprs(stmt) {
+            fact.insert(expr.clone());
+        }
+
+        kill(node, stmt, &mut fact);
+    }
+
+    fact
+}
+
+/// ```plain
+/// join = intersection
+/// ```
+pub fn join<E: Clone + Eq + std::hash::Hash>(facts: Vec<PowerSet<E, Must>>) -> PowerSet<E, Must> {
+    join_all(facts)
+}
+
+/// Find the available expressions for every node in `graph`
+pub fn solve<N, G>(graph: &G) -> HashMap<N::NodeId, NodeInfo<PowerSet<N::Expr, Must>>>
+where
+    N: Expressions,
+    N::NodeId: std::fmt::Debug,
+    G: Graph<N>,
+{
+    Analyzer::new_forward(PowerSet::all(), trans, join)
+        .with_entry_fact(PowerSet::empty())
+        .solve(graph)
+}
@@ -0,0 +1,55 @@
+//! Very busy expressions analysis, also known as anticipated expressions,
+//! finds all expressions which will be evaluated on every path from a given
+//! point before any of their operands are assigned.
+//!
+//! Very busy expressions is a backwards must problem with these functions
+//!
+//! ```plain
+//! trans(b) = union(gen(b), out(b) - kill(b))
+//! join = intersection
+//! ```
+//!
+//! where `gen(b)` gives the expressions evaluated in `b` before any of their
+//! operands are assigned in `b`, and `kill(b)` gives the expressions with an
+//! operand assigned in `b`. Nothing is very busy on exit from the graph.
+
+use super::{kill, Expressions};
+use crate::lattice::{join_all, Must, PowerSet};
+use crate::{Analyzer, Graph, HashMap, NodeInfo};
+
+/// ```plain
+/// trans(b) = union(gen(b), out(b) - kill(b))
+/// ```
+pub fn trans<N: Expressions>(
+    node: &N,
+    mut fact: PowerSet<N::Expr, Must>,
+) -> PowerSet<N::Expr, Must> {
+    for stmt in (0..node.stmt_count()).rev() {
+        kill(node, stmt, &mut fact);
+
+        for expr in node.exprs(stmt) {
+            fact.insert(expr.clone());
+        }
+    }
+
+    fact
+}
+
+/// ```plain
+/// join = intersection
+/// ```
+pub fn join<E: Clone + Eq + std::hash::Hash>(facts: Vec<PowerSet<E, Must>>) -> PowerSet<E, Must> {
+    join_all(facts)
+}
+
+/// Find the very busy expressions for every node in `graph`
+pub fn solve<N, G>(graph: &G) -> HashMap<N::NodeId, NodeInfo<PowerSet<N::Expr, Must>>>
+where
+    N: Expressions,
+    N::NodeId: std::fmt::Debug,
+    G: Graph<N>,
+{
+    Analyzer::new_backward(PowerSet::all(), trans, join)
+        .with_exit_fact(PowerSet::empty())
+        .solve(graph)
+}
@@ -0,0 +1,62 @@
+//! Constant propagation finds the variables which hold the same constant
+//! value on every path to a given point.
+//!
+//! Constant propagation is a forwards problem over an environment mapping
+//! every variable to a `Flat` value, with these functions
+//!
+//! ```plain
+//! trans(b) = in(b) with every assignment in b evaluated in order
+//! join = pointwise join
+//! ```
+//!
+//! A variable is `Bottom` before it is assigned, a `Const` when it is known to
+//! hold exactly one value, and `Top` otherwise. Declared variables have no
+//! value yet, and so are `Top`.
+//!
+//! Unlike the other bundled analyses, this needs to know what statements
+//! compute, so it only works on the built-in statement language.
+
+use crate::cfg::{Block, BlockId, NodeGraph, Statement, Variable};
+use crate::lattice::{join_all, Flat, MapLattice};
+use crate::{Analyzer, HashMap, NodeInfo};
+
+/// The value of every variable
+pub type Env = MapLattice<Variable, Flat<i32>>;
+
+/// ```plain
+/// trans(b) = in(b) with every assignment in b evaluated in order
+/// ```
+pub fn trans(block: &Block<Vec<Statement>>, mut env: Env) -> Env {
+    for stmt in block.data.iter() {
+        match stmt {
+            Statement::Declare(var) => env.insert(*var, Flat::Top),
+            Statement::ConstAssign(var, value) => env.insert(*var, Flat::Const(*value)),
+            Statement::VarAssign(var, war) => {
+                let value = env.get(war);
+                env.insert(*var, value);
+            }
+            Statement::ExprAssign(var, expr) => {
+                let [lhs, rhs] = expr.args;
+                let value = env
+                    .get(&lhs)
+                    .map2(&env.get(&rhs), |a, b| expr.op.apply(*a, *b));
+
+                env.insert(*var, value);
+            }
+        }
+    }
+
+    env
+}
+
+/// ```plain
+/// join = pointwise join
+/// ```
+pub fn join(envs: Vec<Env>) -> Env {
+    join_all(envs)
+}
+
+/// Find the constant variables for every block in `graph`
+pub fn solve(graph: &NodeGraph<Vec<Statement>>) -> HashMap<BlockId, NodeInfo<Env>> {
+    Analyzer::new_forward_lattice(trans).solve(graph)
+}
@@ -0,0 +1,74 @@
+//! Interval analysis finds a range of values for every variable at every
+//! point.
+//!
+//! Interval analysis is a forwards problem over an environment mapping every
+//! variable to an `Interval`, with these functions
+//!
+//! ```plain
+//! trans(b) = in(b) with every assignment in b evaluated in order
+//! join = pointwise join
+//! ```
+//!
+//! Intervals can grow forever around loops, so the solve widens the facts at
+//! the head of every loop. Arithmetic in the statement language wraps on
+//! overflow, so any result which may not fit in an `i32` could be anything.
+//! Declared variables have no value yet, and so could also be anything.
+
+use crate::cfg::{BinOp, Block, BlockId, NodeGraph, Statement, Variable};
+use crate::lattice::{join_all, Interval, MapLattice, Widen};
+use crate::{Analyzer, HashMap, NodeInfo};
+
+/// The range of every variable
+pub type Env = MapLattice<Variable, Interval>;
+
+/// ```plain
+/// trans(b) = in(b) with every assignment in b evaluated in order
+/// ```
+pub fn trans(block: &Block<Vec<Statement>>, mut env: Env) -> Env {
+    for stmt in block.data.iter() {
+        match stmt {
+            Statement::Declare(var) => env.insert(*var, Interval::full()),
+            Statement::ConstAssign(var, value) => {
+                env.insert(*var, Interval::constant(i64::from(*value)))
+            }
+            Statement::VarAssign(var, war) => {
+                let value = env.get(war);
+                env.insert(*var, value);
+            }
+            Statement::ExprAssign(var, expr) => {
+                let [lhs, rhs] = expr.args;
+                let (lhs, rhs) = (env.get(&lhs), env.get(&rhs));
+                let value = match expr.op {
+                    BinOp::Add => lhs + rhs,
+                    BinOp::Sub => lhs - rhs,
+                    BinOp::Mul => lhs * rhs,
+                };
+
+                let bits = Interval::new(i64::from(i32::MIN), i64::from(i32::MAX));
+                let value = if value.is_subset(&bits) {
+                    value
+                } else {
+                    Interval::full()
+                };
+
+                env.insert(*var, value);
+            }
+        }
+    }
+
+    env
+}
+
+/// ```plain
+/// join = pointwise join
+/// ```
+pub fn join(envs: Vec<Env>) -> Env {
+    join_all(envs)
+}
+
+/// Find the range of every variable for every block in `graph`
+pub fn solve(graph: &NodeGraph<Vec<Statement>>) -> HashMap<BlockId, NodeInfo<Env>> {
+    Analyzer::new_forward_lattice(trans)
+        .with_widening(Env::widen)
+        .solve(graph)
+}
@@ -0,0 +1,66 @@
+//! Liveness analysis finds all variables which may be used after a given point.
+//!
+//! Liveness analysis is a backwards problem with these functions
+//!
+//! ```plain
+//! trans(b) = union(gen(b), in(b) - kill(b))
+//! join = union
+//! ```
+//!
+//! where `gen(b)` gives the variables used before being assigned, and
+//! `kill(b)` gives the variables assigned.
+
+use super::DefUse;
+use crate::{Analyzer, Fact, Graph, HashMap, HashSet, NodeInfo};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LiveVars<V: Eq + std::hash::Hash> {
+    pub live: HashSet<V>,
+}
+
+impl<V: Eq + std::hash::Hash> LiveVars<V> {
+    pub fn new(live: HashSet<V>) -> Self {
+        Self { live }
+    }
+}
+
+impl<V: Clone + Eq + std::hash::Hash> Fact for LiveVars<V> {}
+
+/// ```plain
+/// trans(b) = union(gen(b), in(b) - kill(b))
+/// ```
+pub fn trans<N: DefUse>(node: &N, mut fact: LiveVars<N::Var>) -> LiveVars<N::Var> {
+    for stmt in (0..node.stmt_count()).rev() {
+        for var in node.defs(stmt) {
+            fact.live.remove(var);
+        }
+
+        fact.live.extend(node.uses(stmt).iter().copied());
+    }
+
+    fact
+}
+
+/// ```plain
+/// join = union
+/// ```
+pub fn join<V: Copy + Eq + std::hash::Hash>(facts: Vec<LiveVars<V>>) -> LiveVars<V> {
+    let mut live = HashSet::default();
+
+    for fact in facts {
+        live.extend(fact.live);
+    }
+
+    LiveVars { live }
+}
+
+/// Find the live variables for every node in `graph`
+pub fn solve<N, G>(graph: &G) -> HashMap<N::NodeId, NodeInfo<LiveVars<N::Var>>>
+where
+    N: DefUse,
+    N::NodeId: std::fmt::Debug,
+    G: Graph<N>,
+{
+    let top = LiveVars::new(HashSet::default());
+    Analyzer::new_backward(top, trans, join).solve(graph)
+}
@@ -0,0 +1,127 @@
+//! Standard analyses which work on any node that can describe the variables
+//! it uses and assigns.
+//!
+//! Every analysis lives in its own module with its fact type, its `trans` and
+//! `join` functions, and a `solve` function which runs it on a graph.
+
+pub mod assign;
+pub mod available;
+pub mod busy;
+pub mod constants;
+pub mod intervals;
+pub mod live;
+pub mod reaching;
+
+use std::hash::Hash;
+
+use super::cfg::{Block, BlockId, Expr, Statement, Variable};
+use super::lattice::{Must, PowerSet};
+use super::Node;
+
+/// A node made up of a sequence of statements, each of which may use, define,
+/// or declare variables. Within a statement, uses happen before definitions.
+pub trait DefUse: Node {
+    /// A small value used to uniquely identify a variable
+    type Var: Copy + Eq + Hash;
+
+    /// Get the number of statements in this node
+    fn stmt_count(&self) -> usize;
+
+    /// Get the variables read by statement `stmt`
+    fn uses(&self, stmt: usize) -> &[Self::Var];
+
+    /// Get the variables assigned by statement `stmt`
+    fn defs(&self, stmt: usize) -> &[Self::Var];
+
+    /// Get the variables declared without a value by statement `stmt`
+    fn decls(&self, _stmt: usize) -> &[Self::Var] {
+        &[]
+    }
+}
+
+impl DefUse for Block<Vec<Statement>> {
+    type Var = Variable;
+
+    fn stmt_count(&self) -> usize {
+        self.data.len()
+    }
+
+    fn uses(&self, stmt: usize) -> &[Variable] {
+        match &self.data[stmt] {
+            Statement::VarAssign(_, war) => std::slice::from_ref(war),
+            Statement::ExprAssign(_, expr) => &expr.args,
+            _ => &[],
+        }
+    }
+
+    fn defs(&self, stmt: usize) -> &[Variable] {
+        match &self.data[stmt] {
+            Statement::ConstAssign(var, _)
+            | Statement::VarAssign(var, _)
+            | Statement::ExprAssign(var, _) => std::slice::from_ref(var),
+            Statement::Declare(_) => &[],
+        }
+    }
+
+    fn decls(&self, stmt: usize) -> &[Variable] {
+        match &self.data[stmt] {
+            Statement::Declare(var) => std::slice::from_ref(var),
+            _ => &[],
+        }
+    }
+}
+
+/// A node which knows its own id, so that facts can name the statements in it
+pub trait Located: Node {
+    /// Get the id of this node
+    fn id(&self) -> Self::NodeId;
+}
+
+impl<T> Located for Block<T> {
+    fn id(&self) -> BlockId {
+        self.id
+    }
+}
+
+/// A node whose statements evaluate expressions. An expression is killed by a
+/// statement which defines one of its operands.
+pub trait Expressions: DefUse {
+    /// An expression, such as `a + b`
+    type Expr: Clone + Eq + Hash;
+
+    /// Get the expressions evaluated by statement `stmt`. These are evaluated
+    /// before the statement defines anything.
+    fn exprs(&self, stmt: usize) -> &[Self::Expr];
+
+    /// Get the variables read by an expression
+    fn operands(expr: &Self::Expr) -> &[Self::Var];
+}
+
+impl Expressions for Block<Vec<Statement>> {
+    type Expr = Expr;
+
+    fn exprs(&self, stmt: usize) -> &[Expr] {
+        match &self.data[stmt] {
+            Statement::ExprAssign(_, expr) => std::slice::from_ref(expr),
+            _ => &[],
+        }
+    }
+
+    fn operands(expr: &Expr) -> &[Variable] {
+        &expr.args
+    }
+}
+
+/// Remove the expressions in `fact` which are killed by statement `stmt`.
+/// The set of every expression is left as it is, since it can't be listed.
+/// The solver only gives it to nodes before any facts flow into them, or to
+/// nodes which can't be reached, where every expression holds anyway.
+fn kill<N: Expressions>(node: &N, stmt: usize, fact: &mut PowerSet<N::Expr, Must>) {
+    if fact.is_all() {
+        return;
+    }
+
+    for var in node.defs(stmt) {
+        fact.retain(|expr| !N::operands(expr).contains(var));
+    }
+}
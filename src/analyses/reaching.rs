@@ -0,0 +1,201 @@
+//! Reaching definitions analysis finds all definitions which may reach a
+//! given point without being overwritten.
+//!
+//! Reaching definitions is a forwards problem with these functions
+//!
+//! ```plain
+//! trans(b) = union(gen(b), in(b) - kill(b))
+//! join = union
+//! ```
+//!
+//! where `gen(b)` gives the definitions in `b` which aren't overwritten later
+//! in `b`, and `kill(b)` gives every other definition of the variables
+//! assigned in `b`.
+//!
+//! The solution is most often used to build def-use and use-def chains, which
+//! `def_use` and `use_def` do.
+
+use std::hash::Hash;
+
+use super::{DefUse, Located};
+use crate::{Analyzer, Fact, Graph, HashMap, HashSet, NodeInfo};
+
+/// A definition of `var` by statement `stmt` in `node`
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Def<Id, V> {
+    pub node: Id,
+    pub stmt: usize,
+    pub var: V,
+}
+
+/// A use of `var` by statement `stmt` in `node`
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Use<Id, V> {
+    pub node: Id,
+    pub stmt: usize,
+    pub var: V,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReachingDefs<Id: Eq + Hash, V: Eq + Hash> {
+    pub defs: HashSet<Def<Id, V>>,
+}
+
+impl<Id: Eq + Hash, V: Eq + Hash> ReachingDefs<Id, V> {
+    pub fn new(defs: HashSet<Def<Id, V>>) -> Self {
+        Self { defs }
+    }
+}
+
+impl<Id: Clone + Eq + Hash, V: Clone + Eq + Hash> Fact for ReachingDefs<Id, V> {}
+
+/// The facts for every node in a graph
+pub type Solution<N> = HashMap<
+    <N as crate::Node>::NodeId,
+    NodeInfo<ReachingDefs<<N as crate::Node>::NodeId, <N as DefUse>::Var>>,
+>;
+
+/// Maps every use to the definitions which may reach it
+pub type UseDefChains<Id, V> = HashMap<Use<Id, V>, HashSet<Def<Id, V>>>;
+
+/// Maps every definition to the uses it may reach
+pub type DefUseChains<Id, V> = HashMap<Def<Id, V>, HashSet<Use<Id, V>>>;
+
+/// ```plain
+/// trans(b) = union(gen(b), in(b) - kill(b))
+/// ```
+pub fn trans<N: DefUse + Located>(
+    node: &N,
+    mut fact: ReachingDefs<N::NodeId, N::Var>,
+) -> ReachingDefs<N::NodeId, N::Var> {
+    let id = node.id();
+
+    for stmt in 0..node.stmt_count() {
+        for var in node.defs(stmt) {
+            fact.defs.retain(|def| def.var != *var);
+            fact.defs.insert(Def {
+                node: id,
+                stmt,
+                var: *var,
+            });
+        }
+    }
+
+    fact
+}
+
+/// ```plain
+/// join = union
+/// ```
+pub fn join<Id, V>(facts: Vec<ReachingDefs<Id, V>>) -> ReachingDefs<Id, V>
+where
+    Id: Copy + Eq + Hash,
+    V: Copy + Eq + Hash,
+{
+    let mut defs = HashSet::default();
+
+    for fact in facts {
+        defs.extend(fact.defs);
+    }
+
+    ReachingDefs { defs }
+}
+
+/// Find the reaching definitions for every node in `graph`
+pub fn solve<N, G>(graph: &G) -> Solution<N>
+where
+    N: DefUse + Located,
+    N::NodeId: std::fmt::Debug,
+    G: Graph<N>,
+{
+    let top = ReachingDefs::new(HashSet::default());
+    Analyzer::new_forward(top, trans, join).solve(graph)
+}
+
+/// Build the use-def chains from a solution, mapping every use to the
+/// definitions which may reach it. Uses which no definition reaches map to an
+/// empty set. Nodes missing from `res` are skipped.
+pub fn use_def<N, G>(graph: &G, res: &Solution<N>) -> UseDefChains<N::NodeId, N::Var>
+where
+    N: DefUse,
+    G: Graph<N>,
+{
+    let mut chains = HashMap::default();
+
+    for id in graph.get_all_node_ids() {
+        let info = match res.get(id) {
+            Some(info) => info,
+            None => continue,
+        };
+
+        let node = graph.get(*id);
+        let mut reaching = info.before.clone();
+
+        for stmt in 0..node.stmt_count() {
+            for var in node.uses(stmt) {
+                let defs = reaching
+                    .defs
+                    .iter()
+                    .filter(|def| def.var == *var)
+                    .copied()
+                    .collect();
+                let use_ = Use {
+                    node: *id,
+                    stmt,
+                    var: *var,
+                };
+
+                chains.insert(use_, defs);
+            }
+
+            for var in node.defs(stmt) {
+                reaching.defs.retain(|def| def.var != *var);
+                reaching.defs.insert(Def {
+                    node: *id,
+                    stmt,
+                    var: *var,
+                });
+            }
+        }
+    }
+
+    chains
+}
+
+/// Build the def-use chains from a solution, mapping every definition to the
+/// uses it may reach. Definitions which reach no use map to an empty set.
+/// Nodes missing from `res` are skipped.
+pub fn def_use<N, G>(graph: &G, res: &Solution<N>) -> DefUseChains<N::NodeId, N::Var>
+where
+    N: DefUse,
+    G: Graph<N>,
+{
+    let mut chains: HashMap<_, HashSet<_>> = HashMap::default();
+
+    for id in graph.get_all_node_ids() {
+        if !res.contains_key(id) {
+            continue;
+        }
+
+        let node = graph.get(*id);
+        for stmt in 0..node.stmt_count() {
+            for var in node.defs(stmt) {
+                let def = Def {
+                    node: *id,
+                    stmt,
+                    var: *var,
+                };
+
+                chains.insert(def, HashSet::default());
+            }
+        }
+    }
+
+    for (use_, defs) in use_def(graph, res) {
+        for def in defs {
+            chains.entry(def).or_default().insert(use_);
+        }
+    }
+
+    chains
+}
@@ -1,8 +1,49 @@
 use std::collections::VecDeque;
 
+use super::lattice::{self, Lattice};
+use super::mop::{self, Difference};
+use super::observe::{DefaultObserver, Observer, Stats};
+#[cfg(feature = "rayon")]
+use super::parallel;
 use super::problem::{Backward, Forward, Problem};
-use super::HashMap;
+use super::query::Query;
+use super::steps::Steps;
 use super::{Fact, Graph, Node, NodeInfo};
+use super::{HashMap, HashSet};
+
+/// A widening operator, given the previous and the newly joined fact
+type Widening<F> = Box<dyn FnMut(&F, &F) -> F>;
+
+/// The facts for every node of a graph
+type Infos<Id, F> = HashMap<Id, NodeInfo<F>>;
+
+/// A solve which was cancelled before it reached a fixed point
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cancelled<F: Fact, Id: Eq + std::hash::Hash> {
+    /// The facts computed so far, which may not hold yet. Nodes which haven't
+    /// been visited may be missing.
+    pub infos: Infos<Id, F>,
+
+    /// The nodes which were still waiting to be visited
+    pub worklist: Vec<Id>,
+}
+
+impl<F: Fact, Id: Eq + std::hash::Hash> std::fmt::Display for Cancelled<F, Id> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "solve cancelled with {} nodes left to visit",
+            self.worklist.len()
+        )
+    }
+}
+
+impl<F, Id> std::error::Error for Cancelled<F, Id>
+where
+    F: Fact + std::fmt::Debug,
+    Id: Eq + std::hash::Hash + std::fmt::Debug,
+{
+}
 
 pub struct Analyzer<F, N, G, Trans, Join, Sort>
 where
@@ -26,6 +67,13 @@ where
     /// Join function which joins multiple facts
     join: Join,
 
+    /// Widening operator, which is applied to the joined facts of nodes at the
+    /// head of a cycle
+    widen: Option<Widening<F>>,
+
+    /// Nodes whose joined facts are widened
+    widen_at: HashSet<N::NodeId>,
+
     infos: HashMap<N::NodeId, NodeInfo<F>>,
 
     _graph: std::marker::PhantomData<G>,
@@ -57,6 +105,9 @@ where
             trans,
             join,
 
+            widen: None,
+            widen_at: HashSet::default(),
+
             infos: HashMap::default(),
 
             _graph: std::marker::PhantomData,
@@ -65,6 +116,8 @@ where
         }
     }
 
+    /// Set the fact which holds on entry to the graph. This is joined with the
+    /// facts from the entry node's predecessors, if it has any.
     pub fn with_entry_fact(self, enter: F) -> Self {
         Self {
             first_fact: NodeInfo {
@@ -100,6 +153,9 @@ where
             trans,
             join,
 
+            widen: None,
+            widen_at: HashSet::default(),
+
             infos: HashMap::default(),
 
             _graph: std::marker::PhantomData,
@@ -108,6 +164,8 @@ where
         }
     }
 
+    /// Set the fact which holds on exit from the graph. This is joined with
+    /// the facts from the exit node's successors, if it has any.
     pub fn with_exit_fact(self, exit: F) -> Self {
         Self {
             first_fact: NodeInfo {
@@ -119,6 +177,34 @@ where
     }
 }
 
+impl<F, N, G, Trans> Analyzer<F, N, G, Trans, fn(Vec<F>) -> F, Forward>
+where
+    F: Lattice,
+    N: Node,
+    G: Graph<N>,
+    Trans: FnMut(&N, F) -> F,
+{
+    /// Create a new forwards problem over a lattice, which starts from
+    /// `bottom` and joins with `lattice::join_all`
+    pub fn new_forward_lattice(trans: Trans) -> Self {
+        Self::new_forward(F::bottom(), trans, lattice::join_all)
+    }
+}
+
+impl<F, N, G, Trans> Analyzer<F, N, G, Trans, fn(Vec<F>) -> F, Backward>
+where
+    F: Lattice,
+    N: Node,
+    G: Graph<N>,
+    Trans: FnMut(&N, F) -> F,
+{
+    /// Create a new backwards problem over a lattice, which starts from
+    /// `bottom` and joins with `lattice::join_all`
+    pub fn new_backward_lattice(trans: Trans) -> Self {
+        Self::new_backward(F::bottom(), trans, lattice::join_all)
+    }
+}
+
 impl<F, N, G, Trans, Join, Sort> Analyzer<F, N, G, Trans, Join, Sort>
 where
     F: Fact,
@@ -129,44 +215,293 @@ where
     Join: FnMut(Vec<F>) -> F,
     Sort: Problem<F, N, G>,
 {
+    /// Widen the facts at the head of every cycle with `widen`, which is given
+    /// the previous and the newly joined fact. This makes the solve terminate
+    /// for facts with infinite ascending chains, such as intervals, as long as
+    /// repeatedly widening an increasing chain of facts eventually stabilizes.
+    pub fn with_widening<W>(self, widen: W) -> Self
+    where
+        W: FnMut(&F, &F) -> F + 'static,
+    {
+        Self {
+            widen: Some(Box::new(widen)),
+            ..self
+        }
+    }
+
+    /// Solve the problem for `graph`, returning the facts for every node
     pub fn solve(&mut self, graph: &G) -> HashMap<N::NodeId, NodeInfo<F>> {
-        // Initialize info map
-        self.infos.clear();
-        let first = Sort::get_first(graph);
-        self.infos.insert(first, self.first_fact.clone());
+        let mut observer = DefaultObserver;
+        self.solve_observed(graph, &mut observer)
+    }
+
+    /// Solve the problem for `graph` like `solve`, notifying `observer` of
+    /// every step the solver takes
+    pub fn solve_observed<O>(
+        &mut self,
+        graph: &G,
+        observer: &mut O,
+    ) -> HashMap<N::NodeId, NodeInfo<F>>
+    where
+        O: Observer<N, F>,
+    {
+        self.solve_until(graph, observer, || false)
+            .unwrap_or_else(|cancelled| cancelled.infos)
+    }
+
+    /// Solve the problem for `graph` like `solve`, but give up as soon as
+    /// `cancelled` returns `true`. It is called before every visit to a node,
+    /// so it should be cheap, like checking a flag set by another thread.
+    pub fn solve_cancellable<C>(
+        &mut self,
+        graph: &G,
+        cancelled: C,
+    ) -> Result<Infos<N::NodeId, F>, Cancelled<F, N::NodeId>>
+    where
+        C: FnMut() -> bool,
+    {
+        let mut observer = DefaultObserver;
+        self.solve_until(graph, &mut observer, cancelled)
+    }
+
+    /// Solve the problem for `graph` one worklist step at a time. Each item of
+    /// the returned iterator describes a single visit to a node.
+    pub fn solve_steps<'a>(&'a mut self, graph: &'a G) -> Steps<'a, F, N, G, Trans, Join, Sort> {
+        Steps::new(self, graph)
+    }
+
+    /// Solve the problem for `graph` like `solve`, solving the parts of the
+    /// graph which don't depend on each other in parallel. Each strongly
+    /// connected component of the graph is solved on a single thread, once
+    /// all the components it depends on are done.
+    ///
+    /// This gives the same facts as `solve` for any monotone `trans`. Widening
+    /// depends on the order nodes are visited in, so with a widening operator
+    /// this falls back to `solve`.
+    #[cfg(feature = "rayon")]
+    pub fn solve_parallel(&mut self, graph: &G) -> HashMap<N::NodeId, NodeInfo<F>>
+    where
+        F: Send + Sync,
+        N: Sync,
+        N::NodeId: Send + Sync,
+        G: Sync,
+        Trans: Fn(&N, F) -> F + Sync,
+        Join: Fn(Vec<F>) -> F + Sync,
+    {
+        if self.widen.is_some() {
+            return self.solve(graph);
+        }
+
+        parallel::solve::<_, _, _, _, _, Sort>(
+            graph,
+            &self.first_fact,
+            &self.init_fact,
+            &self.trans,
+            &self.join,
+        )
+    }
+
+    /// Solve the problem for `graph` by joining the facts given by every path
+    /// with at most `max_len` nodes, rather than iterating to a fixed point.
+    /// Paths start at the starting node, at any other node with nothing to
+    /// join, and at the cycles none of those reach. This takes exponential
+    /// time, and is meant as a reference for small acyclic graphs, or graphs
+    /// whose loops are unrolled up to `max_len`.
+    pub fn solve_mop(&mut self, graph: &G, max_len: usize) -> HashMap<N::NodeId, NodeInfo<F>> {
+        let first = Sort::get_joined_fact(&self.first_fact).clone();
+        mop::solve::<_, _, _, _, _, Sort>(
+            graph,
+            first,
+            &self.init_fact,
+            &mut self.trans,
+            &mut self.join,
+            max_len,
+        )
+    }
+
+    /// Find the nodes whose facts from `solve` differ from the facts from
+    /// `solve_mop`. For a distributive `trans` and a graph whose paths all
+    /// have at most `max_len` nodes there should be none, so any difference
+    /// points at a `trans` which isn't distributive, or isn't monotone.
+    pub fn check_mop(&mut self, graph: &G, max_len: usize) -> Vec<Difference<F, N::NodeId>> {
+        let mfp = self.solve(graph);
+        let mut mop = self.solve_mop(graph, max_len);
+
+        graph
+            .get_all_node_ids()
+            .iter()
+            .filter_map(|id| {
+                let mop = mop.remove(id)?;
+                let mfp = mfp.get(id)?.clone();
+
+                if mfp == mop {
+                    None
+                } else {
+                    Some(Difference { id: *id, mfp, mop })
+                }
+            })
+            .collect()
+    }
+
+    /// Answer queries for the facts of single nodes of `graph`, only solving
+    /// the nodes each answer depends on. Answers are remembered, so later
+    /// queries only solve the nodes no earlier query needed.
+    pub fn query<'a>(&'a mut self, graph: &'a G) -> Query<'a, F, N, G, Trans, Join, Sort> {
+        Query::new(self, graph)
+    }
+
+    /// Run the worklist to a fixed point, notifying `observer` of every step,
+    /// unless `cancelled` returns `true` before a visit. `finished` is only
+    /// called if the fixed point is reached.
+    fn solve_until<O, C>(
+        &mut self,
+        graph: &G,
+        observer: &mut O,
+        mut cancelled: C,
+    ) -> Result<Infos<N::NodeId, F>, Cancelled<F, N::NodeId>>
+    where
+        O: Observer<N, F>,
+        C: FnMut() -> bool,
+    {
+        let mut stats = Stats::default();
+        let mut worklist = self.start(graph, &mut stats);
+
+        while !worklist.is_empty() {
+            if cancelled() {
+                return Err(Cancelled {
+                    infos: self.take_infos(),
+                    worklist: worklist.into_iter().collect(),
+                });
+            }
+
+            let id = worklist.pop_front().unwrap();
+            self.visit(graph, id, &mut worklist, observer, &mut stats);
+        }
+
+        observer.finished(&stats);
+        Ok(self.take_infos())
+    }
+
+    /// Reset the info map and create the initial worklist for `graph`
+    pub(crate) fn start(&mut self, graph: &G, stats: &mut Stats<N::NodeId>) -> VecDeque<N::NodeId> {
+        self.reset(graph);
 
         // Initialize worklist
         // We start off with all nodes in the worklist to ensure every node gets
         // visited, even if the `trans`d fact of a particular node isn't
         // different from the initial fact.
+        let first = Sort::get_first(graph);
         let nodes = graph.get_all_node_ids();
         let mut worklist = VecDeque::with_capacity(nodes.len());
         worklist.push_back(first);
         worklist.extend(nodes.iter().skip_while(|n| **n == first));
+        stats.max_worklist_len = worklist.len();
 
-        while let Some(id) = worklist.pop_front() {
-            let node = graph.get(id);
+        worklist
+    }
 
-            // Solve new info
-            let joined = self.solve_joins(graph, id);
-            let transd = (&mut self.trans)(node, joined.clone());
+    /// Reset the info map for `graph`, leaving only the starting node's fact
+    pub(crate) fn reset(&mut self, graph: &G) {
+        self.infos.clear();
+        let first = Sort::get_first(graph);
+        self.infos.insert(first, self.first_fact.clone());
 
-            // Get previous info
-            let init_fact = self.init_fact.clone();
-            let info = self.infos.entry(id).or_insert(init_fact);
-            let prev_trans = Sort::get_join_fact(info);
+        if self.widen.is_some() {
+            self.widen_at = Self::widening_points(graph);
+        }
+    }
 
-            if prev_trans != &transd {
-                for dirty in Sort::get_nexts(graph, id) {
-                    if !worklist.contains(dirty) {
-                        worklist.push_back(*dirty);
-                    }
+    /// Recompute the facts for the node `id`, which has just been taken off
+    /// the worklist, and put the nodes which depend on it back on the
+    /// worklist if it changed. Returns whether the transformed fact changed.
+    pub(crate) fn visit<O>(
+        &mut self,
+        graph: &G,
+        id: N::NodeId,
+        worklist: &mut VecDeque<N::NodeId>,
+        observer: &mut O,
+        stats: &mut Stats<N::NodeId>,
+    ) -> bool
+    where
+        O: Observer<N, F>,
+    {
+        let changed = self.update(graph, id, observer, stats);
+
+        if changed {
+            for dirty in Sort::get_nexts(graph, id) {
+                if !worklist.contains(dirty) {
+                    worklist.push_back(*dirty);
+                    observer.node_enqueued(*dirty, worklist.len());
                 }
             }
 
-            Sort::assign(info, joined, transd);
+            stats.max_worklist_len = stats.max_worklist_len.max(worklist.len());
+        }
+
+        changed
+    }
+
+    /// Recompute the facts for the node `id` without touching any worklist.
+    /// Returns whether the transformed fact changed.
+    pub(crate) fn update<O>(
+        &mut self,
+        graph: &G,
+        id: N::NodeId,
+        observer: &mut O,
+        stats: &mut Stats<N::NodeId>,
+    ) -> bool
+    where
+        O: Observer<N, F>,
+    {
+        let node = graph.get(id);
+        observer.node_visited(id);
+        *stats.visits.entry(id).or_insert(0) += 1;
+
+        // Solve new info
+        let mut joined = self.solve_joins(graph, id);
+        if let Some(widen) = self.widen.as_mut() {
+            if self.widen_at.contains(&id) {
+                if let Some(info) = self.infos.get(&id) {
+                    joined = widen(Sort::get_joined_fact(info), &joined);
+                }
+            }
         }
 
+        let transd = (self.trans)(node, joined.clone());
+        stats.join_calls += 1;
+        stats.trans_calls += 1;
+
+        // Get previous info
+        let init_fact = self.init_fact.clone();
+        let info = self.infos.entry(id).or_insert(init_fact);
+        let prev_trans = Sort::get_join_fact(info);
+        let changed = prev_trans != &transd;
+
+        if changed {
+            observer.fact_changed(id, prev_trans, &transd);
+        }
+
+        Sort::assign(info, joined, transd);
+        changed
+    }
+
+    /// Get the nodes which depend on the facts of `id`
+    pub(crate) fn nexts<'g>(&self, graph: &'g G, id: N::NodeId) -> &'g [N::NodeId] {
+        Sort::get_nexts(graph, id)
+    }
+
+    /// Get the nodes whose facts are joined into the facts of `id`
+    pub(crate) fn joins<'g>(&self, graph: &'g G, id: N::NodeId) -> &'g [N::NodeId] {
+        Sort::get_joins(graph, id)
+    }
+
+    /// Get the facts computed so far
+    pub(crate) fn infos(&self) -> &HashMap<N::NodeId, NodeInfo<F>> {
+        &self.infos
+    }
+
+    /// Take the facts computed so far, leaving the info map empty
+    pub(crate) fn take_infos(&mut self) -> HashMap<N::NodeId, NodeInfo<F>> {
         self.infos.drain().collect()
     }
 
@@ -174,12 +509,63 @@ where
     fn solve_joins(&mut self, graph: &G, node_id: N::NodeId) -> F {
         let mut infos = Vec::new();
 
+        // The starting node also joins in the fact given for it, so that it
+        // isn't lost when the node has no predecessors (in a forwards problem)
+        if node_id == Sort::get_first(graph) {
+            infos.push(Sort::get_joined_fact(&self.first_fact).clone());
+        }
+
         for next in Sort::get_joins(graph, node_id) {
             let init_fact = self.init_fact.clone();
             let next_info = self.infos.entry(*next).or_insert(init_fact);
             infos.push(Sort::get_join_fact(next_info).clone());
         }
 
-        (&mut self.join)(infos)
+        (self.join)(infos)
+    }
+
+    /// Find the nodes which are the target of a back edge in a depth first
+    /// search from the starting node. Every cycle contains at least one of
+    /// them, so widening there is enough to make every cycle stabilize.
+    fn widening_points(graph: &G) -> HashSet<N::NodeId> {
+        let mut points = HashSet::default();
+
+        // Maps each node that has been seen to whether it is still on the stack
+        let mut on_stack = HashMap::default();
+
+        let first = Sort::get_first(graph);
+        for root in std::iter::once(&first).chain(graph.get_all_node_ids()) {
+            if on_stack.contains_key(root) {
+                continue;
+            }
+
+            on_stack.insert(*root, true);
+            let mut stack = vec![(*root, 0)];
+
+            while let Some((id, i)) = stack.last().copied() {
+                match Sort::get_nexts(graph, id).get(i) {
+                    Some(next) => {
+                        stack.last_mut().unwrap().1 += 1;
+
+                        match on_stack.get(next) {
+                            Some(true) => {
+                                points.insert(*next);
+                            }
+                            Some(false) => {}
+                            None => {
+                                on_stack.insert(*next, true);
+                                stack.push((*next, 0));
+                            }
+                        }
+                    }
+                    None => {
+                        on_stack.insert(id, false);
+                        stack.pop();
+                    }
+                }
+            }
+        }
+
+        points
     }
 }
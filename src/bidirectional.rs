@@ -0,0 +1,320 @@
+use super::observe::{DefaultObserver, Observer, Stats};
+use super::{Analyzer, HashMap};
+use super::{Fact, Graph, Node, NodeInfo};
+
+/// A bidirectional problem lets information flow both ways. Each node's
+/// `before` fact depends on its predecessors and on its own `after` fact, and
+/// each node's `after` fact depends on its successors and on its own `before`
+/// fact:
+///
+/// ```plain
+/// before(n) = join(after(p) for p in preds(n), backward(n, after(n)))
+/// after(n) = join(forward(n, before(n)), before(s) for s in succs(n))
+/// ```
+///
+/// This is solved as a forwards problem with an `Analyzer`, over a graph with
+/// a node for each side of every node, and a node for each way a fact flows
+/// through it:
+///
+/// ```plain
+///        +------ forward(n) ------+
+///        |                        v
+/// after(p) ---> before(n)      after(n) ---> ...
+///        ^                        |
+///        +----- backward(n) ------+
+/// ```
+///
+/// so facts are recomputed whenever a fact on either side of them changes,
+/// until nothing changes.
+pub struct Bidirectional<F, N, G, Fwd, Bwd, Join>
+where
+    F: Fact,
+    N: Node,
+    G: Graph<N>,
+    Fwd: FnMut(&N, F) -> F,
+    Bwd: FnMut(&N, F) -> F,
+    Join: FnMut(Vec<F>) -> F,
+{
+    /// Fact which holds on entry to the graph
+    entry_fact: F,
+
+    /// Fact which holds on exit from the graph
+    exit_fact: F,
+
+    /// Initial fact for every node
+    top: F,
+
+    /// Transition function from a node's `before` fact to its `after` fact
+    forward: Fwd,
+
+    /// Transition function from a node's `after` fact to its `before` fact
+    backward: Bwd,
+
+    /// Join function which joins multiple facts
+    join: Join,
+
+    _graph: std::marker::PhantomData<G>,
+    _node: std::marker::PhantomData<N>,
+}
+
+impl<F, N, G, Fwd, Bwd, Join> Bidirectional<F, N, G, Fwd, Bwd, Join>
+where
+    F: Fact,
+    N: Node,
+    N::NodeId: std::fmt::Debug,
+    G: Graph<N>,
+    Fwd: FnMut(&N, F) -> F,
+    Bwd: FnMut(&N, F) -> F,
+    Join: FnMut(Vec<F>) -> F,
+{
+    /// Create a new bidirectional problem with the given top fact, forward and
+    /// backward transformation functions and join function. `top` should have
+    /// the property that `join(vec![f, top]) == f` for all facts `f`
+    pub fn new(top: F, forward: Fwd, backward: Bwd, join: Join) -> Self {
+        Self {
+            entry_fact: top.clone(),
+            exit_fact: top.clone(),
+            top,
+            forward,
+            backward,
+            join,
+
+            _graph: std::marker::PhantomData,
+            _node: std::marker::PhantomData,
+        }
+    }
+
+    /// Set the fact which holds on entry to the graph. This is joined into the
+    /// entry node's `before` fact.
+    pub fn with_entry_fact(self, entry: F) -> Self {
+        Self {
+            entry_fact: entry,
+            ..self
+        }
+    }
+
+    /// Set the fact which holds on exit from the graph. This is joined into
+    /// the exit node's `after` fact.
+    pub fn with_exit_fact(self, exit: F) -> Self {
+        Self {
+            exit_fact: exit,
+            ..self
+        }
+    }
+
+    /// Solve the problem for `graph`, returning the facts for every node
+    pub fn solve(&mut self, graph: &G) -> HashMap<N::NodeId, NodeInfo<F>> {
+        let mut observer = DefaultObserver;
+        self.solve_observed(graph, &mut observer)
+    }
+
+    /// Solve the problem for `graph` like `solve`, notifying `observer` of
+    /// every step the solver takes. Both `before` and `after` facts are
+    /// reported as they change.
+    pub fn solve_observed<O>(
+        &mut self,
+        graph: &G,
+        observer: &mut O,
+    ) -> HashMap<N::NodeId, NodeInfo<F>>
+    where
+        O: Observer<N, F>,
+    {
+        let split = Split::new(graph);
+
+        let forward = &mut self.forward;
+        let backward = &mut self.backward;
+        let exit_fact = &self.exit_fact;
+        let trans = |point: &Point<'_, N>, fact: F| match point.side {
+            Side::Before(_) | Side::After(_) => fact,
+            Side::Forward(_) => forward(point.node.unwrap(), fact),
+            Side::Backward(_) => backward(point.node.unwrap(), fact),
+            Side::Exit => exit_fact.clone(),
+        };
+
+        let mut analyzer = Analyzer::new_forward(self.top.clone(), trans, &mut self.join)
+            .with_entry_fact(self.entry_fact.clone());
+        let res = analyzer.solve_observed(&split, &mut Sides(observer));
+
+        let mut infos = HashMap::default();
+        for id in graph.get_all_node_ids() {
+            let info = NodeInfo {
+                before: res[&Side::Before(*id)].after.clone(),
+                after: res[&Side::After(*id)].after.clone(),
+            };
+
+            infos.insert(*id, info);
+        }
+
+        infos
+    }
+}
+
+/// One of the points of a node in a `Split` graph
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum Side<Id> {
+    /// The `before` fact of a node
+    Before(Id),
+
+    /// The fact given by `forward`, on its way from `before` to `after`
+    Forward(Id),
+
+    /// The `after` fact of a node
+    After(Id),
+
+    /// The fact given by `backward`, on its way from `after` to `before`
+    Backward(Id),
+
+    /// The exit fact, which flows into the exit node's `after`
+    Exit,
+}
+
+struct Point<'g, N: Node> {
+    side: Side<N::NodeId>,
+    node: Option<&'g N>,
+}
+
+impl<'g, N: Node> Node for Point<'g, N> {
+    type NodeId = Side<N::NodeId>;
+}
+
+/// A graph with a point for each side of every node of another graph, and
+/// for each direction a fact flows through it
+struct Split<'g, N: Node> {
+    points: HashMap<Side<N::NodeId>, Point<'g, N>>,
+    preds: HashMap<Side<N::NodeId>, Vec<Side<N::NodeId>>>,
+    succs: HashMap<Side<N::NodeId>, Vec<Side<N::NodeId>>>,
+    ids: Vec<Side<N::NodeId>>,
+    entry: Side<N::NodeId>,
+    exit: Side<N::NodeId>,
+}
+
+impl<'g, N: Node> Split<'g, N> {
+    fn new<G: Graph<N>>(graph: &'g G) -> Self {
+        let mut split = Self {
+            points: HashMap::default(),
+            preds: HashMap::default(),
+            succs: HashMap::default(),
+            ids: Vec::new(),
+            entry: Side::Before(graph.get_entry()),
+            exit: Side::After(graph.get_exit()),
+        };
+
+        for id in graph.get_all_node_ids() {
+            let node = graph.get(*id);
+            let sides = [
+                Side::Before(*id),
+                Side::Forward(*id),
+                Side::After(*id),
+                Side::Backward(*id),
+            ];
+
+            for side in sides.iter() {
+                split.insert(*side, Some(node));
+            }
+        }
+
+        for id in graph.get_all_node_ids() {
+            split.add_edge(Side::Before(*id), Side::Forward(*id));
+            split.add_edge(Side::Forward(*id), Side::After(*id));
+            split.add_edge(Side::After(*id), Side::Backward(*id));
+            split.add_edge(Side::Backward(*id), Side::Before(*id));
+
+            for succ in graph.get_succs(*id) {
+                split.add_edge(Side::After(*id), Side::Before(*succ));
+                split.add_edge(Side::Before(*succ), Side::After(*id));
+            }
+        }
+
+        split.insert(Side::Exit, None);
+        split.add_edge(Side::Exit, split.exit);
+
+        split
+    }
+
+    fn insert(&mut self, side: Side<N::NodeId>, node: Option<&'g N>) {
+        self.points.insert(side, Point { side, node });
+        self.preds.insert(side, Vec::new());
+        self.succs.insert(side, Vec::new());
+        self.ids.push(side);
+    }
+
+    fn add_edge(&mut self, from: Side<N::NodeId>, to: Side<N::NodeId>) {
+        self.succs.get_mut(&from).unwrap().push(to);
+        self.preds.get_mut(&to).unwrap().push(from);
+    }
+}
+
+impl<'g, N: Node> Graph<Point<'g, N>> for Split<'g, N> {
+    fn get(&self, id: Side<N::NodeId>) -> &Point<'g, N> {
+        &self.points[&id]
+    }
+
+    fn get_entry(&self) -> Side<N::NodeId> {
+        self.entry
+    }
+
+    fn get_exit(&self) -> Side<N::NodeId> {
+        self.exit
+    }
+
+    fn get_preds(&self, id: Side<N::NodeId>) -> &[Side<N::NodeId>] {
+        &self.preds[&id]
+    }
+
+    fn get_succs(&self, id: Side<N::NodeId>) -> &[Side<N::NodeId>] {
+        &self.succs[&id]
+    }
+
+    fn get_all_node_ids(&self) -> &[Side<N::NodeId>] {
+        &self.ids
+    }
+}
+
+/// Passes on the events for the `before` and `after` points of a `Split`
+/// graph as events for their nodes
+struct Sides<'o, O>(&'o mut O);
+
+impl<'g, 'o, N, F, O> Observer<Point<'g, N>, F> for Sides<'o, O>
+where
+    N: Node,
+    F: Fact,
+    O: Observer<N, F>,
+{
+    fn node_visited(&mut self, side: Side<N::NodeId>) {
+        if let Side::Before(id) | Side::After(id) = side {
+            self.0.node_visited(id);
+        }
+    }
+
+    fn fact_changed(&mut self, side: Side<N::NodeId>, old: &F, new: &F) {
+        if let Side::Before(id) | Side::After(id) = side {
+            self.0.fact_changed(id, old, new);
+        }
+    }
+
+    fn node_enqueued(&mut self, side: Side<N::NodeId>, len: usize) {
+        if let Side::Before(id) | Side::After(id) = side {
+            self.0.node_enqueued(id, len);
+        }
+    }
+
+    fn finished(&mut self, stats: &Stats<Side<N::NodeId>>) {
+        let mut sides = Stats {
+            max_worklist_len: stats.max_worklist_len,
+            ..Stats::default()
+        };
+
+        for (side, visits) in stats.visits.iter() {
+            match side {
+                Side::Before(id) | Side::After(id) => {
+                    *sides.visits.entry(*id).or_insert(0) += visits;
+                    sides.join_calls += visits;
+                }
+                Side::Forward(_) | Side::Backward(_) => sides.trans_calls += visits,
+                Side::Exit => {}
+            }
+        }
+
+        self.0.finished(&sides);
+    }
+}
@@ -0,0 +1,359 @@
+//! Run one of the bundled analyses on a graph written in the text format, with
+//! statements as payloads, and print the facts for every block.
+
+use std::io::Read;
+use std::process;
+
+use creek::analyses::{assign, available, busy, constants, intervals, live, reaching};
+use creek::cfg::{Block, BlockId, Expr, Statement, Variable};
+use creek::lattice::{Flat, Must, PowerSet};
+use creek::text::{self, StatementGraph};
+use creek::{dot, Fact, Graph, HashMap, HashSet, NodeInfo};
+
+const USAGE: &str = "\
+usage: creek <analysis> [--format <format>] [<file>]
+
+Reads a graph from <file>, or from standard input if no file is given.
+
+analyses:
+    available   expressions computed on every path to a block
+    busy        expressions computed on every path from a block
+    constants   values of variables, as `x = 5`, or `x = ?` if not constant
+    intervals   ranges of variables, as `x = [0, 5]`
+    live        variables which may be used later
+    reaching    definitions which may reach a block, as `var@block.stmt`
+    uninit      variables which may be unassigned
+
+formats:
+    table       one line per block (default)
+    json        an object with the facts for every block
+    dot         a Graphviz graph annotated with facts";
+
+/// Facts shown as sorted lists of strings, so that every analysis can share
+/// the same output code
+#[derive(Clone, Debug, PartialEq)]
+struct Shown(Vec<String>);
+
+impl Fact for Shown {}
+
+type Solution = HashMap<BlockId, NodeInfo<Shown>>;
+
+fn show<F, Show>(res: HashMap<BlockId, NodeInfo<F>>, show: Show) -> Solution
+where
+    F: Fact,
+    Show: Fn(&F) -> Vec<String>,
+{
+    res.into_iter()
+        .map(|(id, info)| {
+            let info = NodeInfo {
+                before: Shown(show(&info.before)),
+                after: Shown(show(&info.after)),
+            };
+
+            (id, info)
+        })
+        .collect()
+}
+
+/// Get the names of a set of variables, in the order they first appear
+fn names(graph: &StatementGraph, vars: &HashSet<Variable>) -> Vec<String> {
+    let mut vars: Vec<_> = vars.iter().copied().collect();
+    vars.sort();
+    vars.into_iter()
+        .map(|v| graph.name(v).to_string())
+        .collect()
+}
+
+fn live(graph: &StatementGraph) -> Solution {
+    show(live::solve(&graph.graph), |f| names(graph, &f.live))
+}
+
+/// Get the expressions in a set, sorted by how they are written
+fn exprs(graph: &StatementGraph, set: &PowerSet<Expr, Must>) -> Vec<String> {
+    let mut exprs: Vec<_> = match set.as_set() {
+        Some(set) => set.iter().map(|e| show_expr(graph, e)).collect(),
+        None => return vec!["*".to_string()],
+    };
+
+    exprs.sort();
+    exprs
+}
+
+fn available(graph: &StatementGraph) -> Solution {
+    show(available::solve(&graph.graph), |f| exprs(graph, f))
+}
+
+fn busy(graph: &StatementGraph) -> Solution {
+    show(busy::solve(&graph.graph), |f| exprs(graph, f))
+}
+
+fn constants(graph: &StatementGraph) -> Solution {
+    show(constants::solve(&graph.graph), |f| {
+        let mut vars: Vec<_> = f.iter().collect();
+        vars.sort_by_key(|(var, _)| **var);
+
+        vars.into_iter()
+            .map(|(var, value)| match value {
+                Flat::Const(value) => format!("{} = {}", graph.name(*var), value),
+                _ => format!("{} = ?", graph.name(*var)),
+            })
+            .collect()
+    })
+}
+
+fn intervals(graph: &StatementGraph) -> Solution {
+    show(intervals::solve(&graph.graph), |f| {
+        let mut vars: Vec<_> = f.iter().collect();
+        vars.sort_by_key(|(var, _)| **var);
+
+        vars.into_iter()
+            .map(|(var, range)| format!("{} = {}", graph.name(*var), range))
+            .collect()
+    })
+}
+
+fn reaching(graph: &StatementGraph) -> Solution {
+    show(reaching::solve(&graph.graph), |f| {
+        let mut defs: Vec<_> = f.defs.iter().collect();
+        defs.sort_by_key(|def| (def.node, def.stmt, def.var));
+
+        defs.into_iter()
+            .map(|def| format!("{}@{}.{}", graph.name(def.var), def.node, def.stmt))
+            .collect()
+    })
+}
+
+fn uninit(graph: &StatementGraph) -> Solution {
+    show(assign::solve(&graph.graph), |f| names(graph, &f.uninit))
+}
+
+enum Format {
+    Table,
+    Json,
+    Dot,
+}
+
+struct Args {
+    analysis: fn(&StatementGraph) -> Solution,
+    format: Format,
+    file: Option<String>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = std::env::args().skip(1);
+    let mut analysis = None;
+    let mut format = Format::Table;
+    let mut file = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Err(String::new()),
+            "-f" | "--format" => {
+                format = match args.next().as_deref() {
+                    Some("table") => Format::Table,
+                    Some("json") => Format::Json,
+                    Some("dot") => Format::Dot,
+                    Some(other) => return Err(format!("unknown format `{}`", other)),
+                    None => return Err("missing format".to_string()),
+                };
+            }
+            _ if analysis.is_none() => {
+                analysis = Some(match arg.as_str() {
+                    "live" => live as fn(&StatementGraph) -> Solution,
+                    "available" => available,
+                    "busy" => busy,
+                    "constants" => constants,
+                    "intervals" => intervals,
+                    "reaching" => reaching,
+                    "uninit" => uninit,
+                    other => return Err(format!("unknown analysis `{}`", other)),
+                });
+            }
+            _ if file.is_none() => file = Some(arg),
+            _ => return Err(format!("unexpected argument `{}`", arg)),
+        }
+    }
+
+    match analysis {
+        Some(analysis) => Ok(Args {
+            analysis,
+            format,
+            file,
+        }),
+        None => Err("missing analysis".to_string()),
+    }
+}
+
+fn read_input(file: &Option<String>) -> std::io::Result<String> {
+    match file {
+        Some(path) => std::fs::read_to_string(path),
+        None => {
+            let mut src = String::new();
+            std::io::stdin().read_to_string(&mut src)?;
+            Ok(src)
+        }
+    }
+}
+
+fn show_stmt(graph: &StatementGraph, stmt: &Statement) -> String {
+    match stmt {
+        Statement::Declare(var) => format!("var {}", graph.name(*var)),
+        Statement::ConstAssign(var, value) => format!("{} = {}", graph.name(*var), value),
+        Statement::VarAssign(var, war) => {
+            format!("{} = {}", graph.name(*var), graph.name(*war))
+        }
+        Statement::ExprAssign(var, expr) => {
+            format!("{} = {}", graph.name(*var), show_expr(graph, expr))
+        }
+    }
+}
+
+fn show_expr(graph: &StatementGraph, expr: &Expr) -> String {
+    let [lhs, rhs] = expr.args;
+    format!("{} {} {}", graph.name(lhs), expr.op, graph.name(rhs))
+}
+
+fn show_fact(fact: &Shown) -> String {
+    format!("{{{}}}", fact.0.join(", "))
+}
+
+fn json_string(s: &str) -> String {
+    let mut res = String::from("\"");
+
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            c if (c as u32) < 0x20 => res.push_str(&format!("\\u{:04x}", c as u32)),
+            c => res.push(c),
+        }
+    }
+
+    res.push('"');
+    res
+}
+
+fn json_fact(fact: &Shown) -> String {
+    let names: Vec<_> = fact.0.iter().map(|s| json_string(s)).collect();
+    format!("[{}]", names.join(", "))
+}
+
+fn table(graph: &StatementGraph, res: &Solution) -> String {
+    let mut rows = vec![(
+        "block".to_string(),
+        "before".to_string(),
+        "after".to_string(),
+    )];
+
+    for block in graph.graph.blocks() {
+        let (before, after) = match res.get(&block.id) {
+            Some(info) => (show_fact(&info.before), show_fact(&info.after)),
+            None => ("-".to_string(), "-".to_string()),
+        };
+
+        rows.push((block.id.to_string(), before, after));
+    }
+
+    let width = |col: fn(&(String, String, String)) -> &String| {
+        rows.iter()
+            .map(|r| col(r).chars().count())
+            .max()
+            .unwrap_or(0)
+    };
+
+    let id_width = width(|r| &r.0);
+    let before_width = width(|r| &r.1);
+
+    let mut out = String::new();
+    for (id, before, after) in &rows {
+        out.push_str(&format!(
+            "{:<iw$}  {:<bw$}  {}\n",
+            id,
+            before,
+            after,
+            iw = id_width,
+            bw = before_width
+        ));
+    }
+
+    out
+}
+
+fn json(graph: &StatementGraph, res: &Solution) -> String {
+    let mut blocks = Vec::new();
+
+    for block in graph.graph.blocks() {
+        let facts = match res.get(&block.id) {
+            Some(info) => format!(
+                ", \"before\": {}, \"after\": {}",
+                json_fact(&info.before),
+                json_fact(&info.after)
+            ),
+            None => String::new(),
+        };
+
+        blocks.push(format!("    {{\"id\": {}{}}}", block.id, facts));
+    }
+
+    format!(
+        "{{\n  \"entry\": {},\n  \"exit\": {},\n  \"blocks\": [\n{}\n  ]\n}}\n",
+        graph.graph.get_entry(),
+        graph.graph.get_exit(),
+        blocks.join(",\n")
+    )
+}
+
+fn graphviz(graph: &StatementGraph, res: &Solution) -> String {
+    let label = |block: &Block<Vec<Statement>>| {
+        let mut label = block.id.to_string();
+
+        for stmt in block.data.iter() {
+            label.push('\n');
+            label.push_str(&show_stmt(graph, stmt));
+        }
+
+        label
+    };
+
+    dot::render_facts(&graph.graph, label, res, show_fact)
+}
+
+fn main() {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(msg) if msg.is_empty() => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(msg) => {
+            eprintln!("error: {}\n\n{}", msg, USAGE);
+            process::exit(2);
+        }
+    };
+
+    let src = match read_input(&args.file) {
+        Ok(src) => src,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    };
+
+    let graph = match text::parse_statements(&src) {
+        Ok(graph) => graph,
+        Err(e) => {
+            let file = args.file.as_deref().unwrap_or("<stdin>");
+            eprintln!("error: {}:{}", file, e);
+            process::exit(1);
+        }
+    };
+
+    let res = (args.analysis)(&graph);
+    let out = match args.format {
+        Format::Table => table(&graph, &res),
+        Format::Json => json(&graph, &res),
+        Format::Dot => graphviz(&graph, &res),
+    };
+
+    print!("{}", out);
+}
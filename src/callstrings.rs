@@ -0,0 +1,267 @@
+//! Context sensitive analysis of whole programs with k-limited call strings.
+//!
+//! Every function is solved separately for each context it is called in,
+//! where a context is the list of the last `k` call sites on the way to it.
+//! Facts only return from a function to the calls made in the same context,
+//! so a larger `k` keeps calls from mixing their facts, at the cost of solving
+//! functions more times. A `k` of 0 gives a single context for every function.
+//!
+//! The same `trans` and `join` as an `Analyzer` are used. A call node's
+//! `trans` gives the fact on entry to its callees, and its `after` fact is the
+//! join of the facts at the exit of its callees:
+//!
+//! ```plain
+//! before(entry(callee), push(ctx, n)) = join(trans(n, before(n, ctx)), ...)
+//! after(n, ctx) = join(after(exit(callee), push(ctx, n)) for callee in callees(n))
+//! ```
+
+use std::collections::VecDeque;
+use std::hash::Hash;
+
+use super::interproc::Program;
+use super::{Fact, Graph, HashMap, HashSet, Node, NodeInfo};
+
+/// A call string, made of the last call sites on the way to a function, as the
+/// function and node which made each call
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Context<FuncId, NodeId> {
+    sites: Vec<(FuncId, NodeId)>,
+}
+
+impl<FuncId: Copy, NodeId: Copy> Context<FuncId, NodeId> {
+    /// The context of the entry function, which nothing has called
+    pub fn empty() -> Self {
+        Self { sites: Vec::new() }
+    }
+
+    /// Get the call sites, from the oldest to the most recent
+    pub fn sites(&self) -> &[(FuncId, NodeId)] {
+        &self.sites
+    }
+
+    /// The context after a call from `node` in `func`, keeping only the last
+    /// `k` call sites
+    pub fn push(&self, func: FuncId, node: NodeId, k: usize) -> Self {
+        let mut sites = self.sites.clone();
+        sites.push((func, node));
+
+        let extra = sites.len().saturating_sub(k);
+        sites.drain(..extra);
+
+        Self { sites }
+    }
+}
+
+/// The facts for every node of every function reached from the entry function,
+/// in every context it was reached in
+pub type Solution<FuncId, NodeId, F> =
+    HashMap<FuncId, HashMap<(NodeId, Context<FuncId, NodeId>), NodeInfo<F>>>;
+
+/// A forward solver for whole programs which keeps the facts of up to `k`
+/// levels of calls apart
+pub struct CallStrings<F, Trans, Join>
+where
+    F: Fact,
+    Join: FnMut(Vec<F>) -> F,
+{
+    /// The number of call sites kept in a context
+    k: usize,
+
+    /// Fact which holds on entry to the entry function
+    entry_fact: F,
+
+    /// Initial fact for every node
+    top: F,
+
+    /// Transition function which finds the fact after a node, or on entry to
+    /// the callees of a call node
+    trans: Trans,
+
+    /// Join function which joins multiple facts
+    join: Join,
+}
+
+/// A function solved in a context
+type Instance<FuncId, NodeId> = (FuncId, Context<FuncId, NodeId>);
+
+/// A node of a function solved in a context
+type Point<FuncId, NodeId> = (FuncId, Context<FuncId, NodeId>, NodeId);
+
+/// The functions solved so far, and which calls reached them
+struct State<FuncId, NodeId, F: Fact> {
+    infos: Solution<FuncId, NodeId, F>,
+    entries: HashMap<Instance<FuncId, NodeId>, F>,
+    callers: HashMap<Instance<FuncId, NodeId>, HashSet<Point<FuncId, NodeId>>>,
+    worklist: VecDeque<Point<FuncId, NodeId>>,
+}
+
+impl<F, Trans, Join> CallStrings<F, Trans, Join>
+where
+    F: Fact,
+    Join: FnMut(Vec<F>) -> F,
+{
+    /// Create a new solver keeping `k` call sites in each context, with the
+    /// given top fact, transformation function and join function. `top`
+    /// should have the property that `join(vec![f, top]) == f` for all facts
+    /// `f`
+    pub fn new(k: usize, top: F, trans: Trans, join: Join) -> Self {
+        Self {
+            k,
+            entry_fact: top.clone(),
+            top,
+            trans,
+            join,
+        }
+    }
+
+    /// Set the fact which holds on entry to the entry function
+    pub fn with_entry_fact(self, entry: F) -> Self {
+        Self {
+            entry_fact: entry,
+            ..self
+        }
+    }
+
+    /// Solve `program` starting from `entry`, returning the facts for every
+    /// node in every context
+    pub fn solve<N, P>(
+        &mut self,
+        program: &P,
+        entry: P::FuncId,
+    ) -> Solution<P::FuncId, N::NodeId, F>
+    where
+        N: Node,
+        P: Program<N>,
+        Trans: FnMut(&N, F) -> F,
+    {
+        let mut state = State {
+            infos: HashMap::default(),
+            entries: HashMap::default(),
+            callers: HashMap::default(),
+            worklist: VecDeque::new(),
+        };
+
+        let root = (entry, Context::empty());
+        state.entries.insert(root.clone(), self.entry_fact.clone());
+        self.reach(program, &mut state, root);
+
+        while let Some((func, ctx, id)) = state.worklist.pop_front() {
+            let graph = program.graph(func);
+            let node = graph.get(id);
+
+            let mut facts = Vec::new();
+            if id == graph.get_entry() {
+                facts.push(state.entries[&(func, ctx.clone())].clone());
+            }
+
+            let infos = &state.infos[&func];
+            facts.extend(
+                graph
+                    .get_preds(id)
+                    .iter()
+                    .map(|p| infos[&(*p, ctx.clone())].after.clone()),
+            );
+            let before = (self.join)(facts);
+
+            let callees = program.callees(func, node);
+            let after = if callees.is_empty() {
+                (self.trans)(node, before.clone())
+            } else {
+                let arg = (self.trans)(node, before.clone());
+                let mut exits = Vec::new();
+
+                for callee in callees {
+                    let key = (*callee, ctx.push(func, id, self.k));
+                    state
+                        .callers
+                        .entry(key.clone())
+                        .or_default()
+                        .insert((func, ctx.clone(), id));
+
+                    // Join the argument into the callee's entry fact, and
+                    // solve it if that changed anything
+                    let old = state.entries.get(&key).cloned();
+                    let new = match &old {
+                        Some(old) => (self.join)(vec![old.clone(), arg.clone()]),
+                        None => arg.clone(),
+                    };
+
+                    if old.as_ref() != Some(&new) {
+                        state.entries.insert(key.clone(), new);
+                        self.reach(program, &mut state, key.clone());
+                    }
+
+                    let exit = program.graph(*callee).get_exit();
+                    exits.push(state.infos[callee][&(exit, key.1)].after.clone());
+                }
+
+                (self.join)(exits)
+            };
+
+            let info = state
+                .infos
+                .get_mut(&func)
+                .unwrap()
+                .get_mut(&(id, ctx.clone()))
+                .unwrap();
+            info.before = before;
+
+            if info.after == after {
+                continue;
+            }
+
+            info.after = after;
+
+            let mut dirty: Vec<_> = graph
+                .get_succs(id)
+                .iter()
+                .map(|s| (func, ctx.clone(), *s))
+                .collect();
+            if id == graph.get_exit() {
+                let key = (func, ctx.clone());
+                dirty.extend(state.callers.get(&key).into_iter().flatten().cloned());
+            }
+
+            for next in dirty {
+                if !state.worklist.contains(&next) {
+                    state.worklist.push_back(next);
+                }
+            }
+        }
+
+        state.infos
+    }
+
+    /// Solve `func` in `ctx` again, setting up its facts if it hasn't been
+    /// reached before
+    fn reach<N, P>(
+        &self,
+        program: &P,
+        state: &mut State<P::FuncId, N::NodeId, F>,
+        (func, ctx): Instance<P::FuncId, N::NodeId>,
+    ) where
+        N: Node,
+        P: Program<N>,
+    {
+        let graph = program.graph(func);
+        let nodes = graph.get_all_node_ids();
+        let infos = state.infos.entry(func).or_default();
+
+        if !infos.contains_key(&(graph.get_entry(), ctx.clone())) {
+            let init = NodeInfo {
+                before: self.top.clone(),
+                after: self.top.clone(),
+            };
+
+            infos.extend(nodes.iter().map(|id| ((*id, ctx.clone()), init.clone())));
+            state
+                .worklist
+                .extend(nodes.iter().map(|id| (func, ctx.clone(), *id)));
+        } else if !state
+            .worklist
+            .contains(&(func, ctx.clone(), graph.get_entry()))
+        {
+            state.worklist.push_back((func, ctx, graph.get_entry()));
+        }
+    }
+}
@@ -0,0 +1,283 @@
+//! A simple built-in control flow graph. A `NodeGraph` consists of one or more
+//! blocks, each of which carries some data of type `T`, such as a list of
+//! statements.
+//!
+//! This module also has a tiny statement language, where each statement is
+//! either a declaration, like `var x`, a constant assignment, like `x = 5`, a
+//! variable assignment, like `x = a`, or an expression assignment, like
+//! `x = a + b`.
+
+use super::HashMap;
+use super::{Graph, Node};
+
+/// A variable with a unique id
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Variable(pub usize);
+
+/// A binary operator
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+}
+
+impl BinOp {
+    /// Apply the operator, wrapping on overflow
+    pub fn apply(self, lhs: i32, rhs: i32) -> i32 {
+        match self {
+            BinOp::Add => lhs.wrapping_add(rhs),
+            BinOp::Sub => lhs.wrapping_sub(rhs),
+            BinOp::Mul => lhs.wrapping_mul(rhs),
+        }
+    }
+}
+
+impl std::fmt::Display for BinOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BinOp::Add => write!(f, "+"),
+            BinOp::Sub => write!(f, "-"),
+            BinOp::Mul => write!(f, "*"),
+        }
+    }
+}
+
+/// An operator applied to two variables, like `a + b`
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Expr {
+    pub op: BinOp,
+    pub args: [Variable; 2],
+}
+
+/// A simple statement, which is either a declaration (`var x`), a constant
+/// assignment (`x = 5`), a variable assignment (`x = a`) or an expression
+/// assignment (`x = a + b`)
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Statement {
+    Declare(Variable),
+    ConstAssign(Variable, i32),
+    VarAssign(Variable, Variable),
+    ExprAssign(Variable, Expr),
+}
+
+/// The unique id for a block
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct BlockId(pub usize);
+
+impl std::fmt::Display for BlockId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A block of data, along with the blocks that point to it and the blocks it
+/// points to
+#[derive(Clone, Debug, PartialEq)]
+pub struct Block<T> {
+    pub id: BlockId,
+    pub data: T,
+    pub preds: Vec<BlockId>,
+    pub succs: Vec<BlockId>,
+}
+
+impl<T> Block<T> {
+    /// Create a block with no edges
+    pub fn new(id: BlockId, data: T) -> Self {
+        Self {
+            id,
+            data,
+            preds: Vec::new(),
+            succs: Vec::new(),
+        }
+    }
+}
+
+impl<T> Node for Block<T> {
+    type NodeId = BlockId;
+}
+
+#[derive(Clone, Debug)]
+pub struct NodeGraph<T> {
+    blocks: HashMap<BlockId, Block<T>>,
+    block_ids: Vec<BlockId>,
+    entry: BlockId,
+    exit: BlockId,
+}
+
+impl<T> NodeGraph<T> {
+    /// Create a `NodeGraph` with the given initial block, which is both the
+    /// entry and the exit block until others are set
+    pub fn new(block: Block<T>) -> Self {
+        let mut graph = Self {
+            blocks: HashMap::default(),
+            block_ids: Vec::new(),
+            entry: block.id,
+            exit: block.id,
+        };
+
+        graph.block_ids.push(block.id);
+        graph.blocks.insert(block.id, block);
+        graph
+    }
+
+    /// Insert a block. Panics if a block with the same id already exists.
+    pub fn insert(&mut self, block: Block<T>) {
+        self.block_ids.push(block.id);
+        if let Some(block) = self.blocks.insert(block.id, block) {
+            panic!("duplicate block {}", block.id);
+        }
+    }
+
+    /// Insert an entry block
+    pub fn insert_entry(&mut self, block: Block<T>) {
+        self.entry = block.id;
+        self.insert(block);
+    }
+
+    /// Insert an exit block
+    pub fn insert_exit(&mut self, block: Block<T>) {
+        self.exit = block.id;
+        self.insert(block);
+    }
+
+    /// Make an existing block the entry block
+    pub fn set_entry(&mut self, id: BlockId) {
+        assert!(self.contains(id), "unknown block {}", id);
+        self.entry = id;
+    }
+
+    /// Make an existing block the exit block
+    pub fn set_exit(&mut self, id: BlockId) {
+        assert!(self.contains(id), "unknown block {}", id);
+        self.exit = id;
+    }
+
+    /// Add an edge from `from` to `to`, updating both blocks. Adding an edge
+    /// which already exists does nothing.
+    pub fn add_edge(&mut self, from: BlockId, to: BlockId) {
+        assert!(self.contains(to), "unknown block {}", to);
+
+        let succs = &mut self.get_mut(from).succs;
+        if succs.contains(&to) {
+            return;
+        }
+
+        succs.push(to);
+        self.get_mut(to).preds.push(from);
+    }
+
+    /// Remove the edge from `from` to `to`, if there is one
+    pub fn remove_edge(&mut self, from: BlockId, to: BlockId) {
+        self.get_mut(from).succs.retain(|id| *id != to);
+        self.get_mut(to).preds.retain(|id| *id != from);
+    }
+
+    /// Check whether the graph has a block with the given id
+    pub fn contains(&self, id: BlockId) -> bool {
+        self.blocks.contains_key(&id)
+    }
+
+    /// Get a mutable reference to a block. Panics if there is no such block.
+    pub fn get_mut(&mut self, id: BlockId) -> &mut Block<T> {
+        self.blocks
+            .get_mut(&id)
+            .unwrap_or_else(|| panic!("unknown block {}", id))
+    }
+
+    /// Get the blocks in the order they were inserted
+    pub fn blocks(&self) -> impl Iterator<Item = &Block<T>> {
+        self.block_ids.iter().map(move |id| &self.blocks[id])
+    }
+
+    /// Get the number of blocks
+    pub fn len(&self) -> usize {
+        self.block_ids.len()
+    }
+
+    /// A graph always has at least one block, so this is always `false`
+    pub fn is_empty(&self) -> bool {
+        self.block_ids.is_empty()
+    }
+
+    /// Get an id which is not used by any block in the graph
+    pub fn fresh_id(&self) -> BlockId {
+        let max = self.block_ids.iter().map(|id| id.0).max().unwrap_or(0);
+        BlockId(max + 1)
+    }
+}
+
+impl<T: Default> NodeGraph<T> {
+    /// Split every critical edge, which is an edge from a block with more than
+    /// one successor to a block with more than one predecessor, by putting a
+    /// new empty block on it. Returns the original edges along with the block
+    /// that was put on each, as `(from, to, new)`.
+    pub fn split_critical_edges(&mut self) -> Vec<(BlockId, BlockId, BlockId)> {
+        let mut critical = Vec::new();
+
+        for block in self.blocks() {
+            if block.succs.len() < 2 {
+                continue;
+            }
+
+            for succ in block.succs.iter() {
+                if self.get(*succ).preds.len() > 1 {
+                    critical.push((block.id, *succ));
+                }
+            }
+        }
+
+        let mut split = Vec::with_capacity(critical.len());
+        for (from, to) in critical {
+            let id = self.fresh_id();
+            let mut block = Block::new(id, T::default());
+            block.preds.push(from);
+            block.succs.push(to);
+            self.insert(block);
+
+            // Keep the new block in the same position as the old edge, so the
+            // order of successors and predecessors stays the same
+            for succ in self.get_mut(from).succs.iter_mut() {
+                if *succ == to {
+                    *succ = id;
+                }
+            }
+
+            for pred in self.get_mut(to).preds.iter_mut() {
+                if *pred == from {
+                    *pred = id;
+                }
+            }
+
+            split.push((from, to, id));
+        }
+
+        split
+    }
+}
+
+impl<T> Graph<Block<T>> for NodeGraph<T> {
+    fn get(&self, id: BlockId) -> &Block<T> {
+        self.blocks.get(&id).unwrap()
+    }
+
+    fn get_entry(&self) -> BlockId {
+        self.entry
+    }
+
+    fn get_exit(&self) -> BlockId {
+        self.exit
+    }
+
+    fn get_preds(&self, id: BlockId) -> &[BlockId] {
+        &self.get(id).preds
+    }
+
+    fn get_succs(&self, id: BlockId) -> &[BlockId] {
+        &self.get(id).succs
+    }
+
+    fn get_all_node_ids(&self) -> &[BlockId] {
+        &self.block_ids
+    }
+}
@@ -0,0 +1,229 @@
+//! Checks for the properties the solver relies on, over sample facts.
+//!
+//! The solver only finds the right fixed point when `join` behaves like the
+//! join of a lattice with `top` as its identity, and `trans` is monotone:
+//!
+//! ```plain
+//! join(a, b) == join(b, a)
+//! join(a, join(b, c)) == join(join(a, b), c)
+//! join(a, a) == a
+//! join(a, top) == a
+//! a <= b  implies  trans(n, a) <= trans(n, b)    where a <= b means join(a, b) == b
+//! ```
+//!
+//! `check_join` and `check_trans` try every combination of the given facts,
+//! single facts before pairs before triples, and report the first one which
+//! breaks a property. Putting the simplest facts first gives the simplest
+//! counterexamples.
+//!
+//! With the `proptest` feature, `prop_join` and `prop_trans` check the same
+//! properties over facts from a `Strategy`, and shrink any counterexample they
+//! find.
+
+use super::{Fact, Graph, Node};
+
+/// A property which doesn't hold, along with the facts which show it
+#[derive(Clone, Debug, PartialEq)]
+pub enum Violation<F, Id = ()> {
+    /// `join(a, b) != join(b, a)`
+    NotCommutative(F, F),
+
+    /// `join(a, join(b, c)) != join(join(a, b), c)`
+    NotAssociative(F, F, F),
+
+    /// `join(a, a) != a`
+    NotIdempotent(F),
+
+    /// `join(a, top) != a`
+    NotIdentity(F),
+
+    /// `smaller <= larger`, but `trans` on the node `id` doesn't keep them in
+    /// that order
+    NotMonotone { id: Id, smaller: F, larger: F },
+}
+
+/// Check that `join` is commutative, associative and idempotent over `facts`,
+/// and that `top` is its identity
+pub fn check_join<F, Join>(facts: &[F], top: &F, mut join: Join) -> Result<(), Violation<F>>
+where
+    F: Fact,
+    Join: FnMut(Vec<F>) -> F,
+{
+    join_laws(facts, top, &mut join)
+}
+
+/// Check that `trans` is monotone on every node of `graph`, for every pair of
+/// facts made from `facts`, using `join` to order them
+pub fn check_trans<F, N, G, Trans, Join>(
+    graph: &G,
+    facts: &[F],
+    mut trans: Trans,
+    mut join: Join,
+) -> Result<(), Violation<F, N::NodeId>>
+where
+    F: Fact,
+    N: Node,
+    G: Graph<N>,
+    Trans: FnMut(&N, F) -> F,
+    Join: FnMut(Vec<F>) -> F,
+{
+    for a in facts {
+        for b in facts {
+            monotone(graph, a, b, &mut trans, &mut join)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn join_laws<F, Id, Join>(facts: &[F], top: &F, join: &mut Join) -> Result<(), Violation<F, Id>>
+where
+    F: Fact,
+    Join: FnMut(Vec<F>) -> F,
+{
+    let mut join2 = |a: &F, b: &F| join(vec![a.clone(), b.clone()]);
+
+    for a in facts {
+        if join2(a, a) != *a {
+            return Err(Violation::NotIdempotent(a.clone()));
+        }
+
+        if join2(a, top) != *a {
+            return Err(Violation::NotIdentity(a.clone()));
+        }
+    }
+
+    for a in facts {
+        for b in facts {
+            if join2(a, b) != join2(b, a) {
+                return Err(Violation::NotCommutative(a.clone(), b.clone()));
+            }
+        }
+    }
+
+    for a in facts {
+        for b in facts {
+            for c in facts {
+                let bc = join2(b, c);
+                let left = join2(a, &bc);
+                let ab = join2(a, b);
+                let right = join2(&ab, c);
+
+                if left != right {
+                    return Err(Violation::NotAssociative(a.clone(), b.clone(), c.clone()));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Check `trans` on `a` and on `join(a, b)`, which is always at least `a`
+fn monotone<F, N, G, Trans, Join>(
+    graph: &G,
+    a: &F,
+    b: &F,
+    trans: &mut Trans,
+    join: &mut Join,
+) -> Result<(), Violation<F, N::NodeId>>
+where
+    F: Fact,
+    N: Node,
+    G: Graph<N>,
+    Trans: FnMut(&N, F) -> F,
+    Join: FnMut(Vec<F>) -> F,
+{
+    let larger = join(vec![a.clone(), b.clone()]);
+
+    for id in graph.get_all_node_ids() {
+        let node = graph.get(*id);
+        let small = trans(node, a.clone());
+        let large = trans(node, larger.clone());
+
+        if join(vec![small, large.clone()]) != large {
+            return Err(Violation::NotMonotone {
+                id: *id,
+                smaller: a.clone(),
+                larger,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "proptest")]
+pub use self::prop::{prop_join, prop_trans};
+
+#[cfg(feature = "proptest")]
+mod prop {
+    use std::cell::RefCell;
+    use std::fmt::Debug;
+
+    use proptest::strategy::Strategy;
+    use proptest::test_runner::{Config, TestCaseError, TestError, TestRunner};
+
+    use super::{join_laws, monotone, Violation};
+    use crate::{Fact, Graph, Node};
+
+    /// A runner which doesn't try to save failures next to the source of the
+    /// caller, since there isn't one
+    fn runner() -> TestRunner {
+        TestRunner::new(Config {
+            failure_persistence: None,
+            ..Config::default()
+        })
+    }
+
+    fn fail<F: Debug, Id: Debug>(violation: Violation<F, Id>) -> TestCaseError {
+        TestCaseError::fail(format!("{:?}", violation))
+    }
+
+    /// Check the laws of `join` like `check_join`, over triples of facts from
+    /// `facts`. A failure gives the smallest triple proptest could shrink it
+    /// to.
+    pub fn prop_join<F, S, Join>(facts: S, top: &F, join: Join) -> Result<(), TestError<(F, F, F)>>
+    where
+        F: Fact + Debug,
+        S: Strategy<Value = F> + Clone,
+        Join: FnMut(Vec<F>) -> F,
+    {
+        let join = RefCell::new(join);
+        let mut runner = runner();
+
+        runner.run(&(facts.clone(), facts.clone(), facts), |(a, b, c)| {
+            join_laws::<_, (), _>(&[a, b, c], top, &mut *join.borrow_mut()).map_err(fail)
+        })
+    }
+
+    /// Check that `trans` is monotone like `check_trans`, over pairs of facts
+    /// from `facts`. A failure gives the smallest pair proptest could shrink
+    /// it to.
+    pub fn prop_trans<F, N, G, S, Trans, Join>(
+        graph: &G,
+        facts: S,
+        trans: Trans,
+        join: Join,
+    ) -> Result<(), TestError<(F, F)>>
+    where
+        F: Fact + Debug,
+        N: Node,
+        N::NodeId: Debug,
+        G: Graph<N>,
+        S: Strategy<Value = F> + Clone,
+        Trans: FnMut(&N, F) -> F,
+        Join: FnMut(Vec<F>) -> F,
+    {
+        let trans = RefCell::new(trans);
+        let join = RefCell::new(join);
+        let mut runner = runner();
+
+        runner.run(&(facts.clone(), facts), |(a, b)| {
+            let trans = &mut *trans.borrow_mut();
+            let join = &mut *join.borrow_mut();
+
+            monotone(graph, &a, &b, trans, join).map_err(fail)
+        })
+    }
+}
@@ -0,0 +1,155 @@
+//! Rendering graphs in the [DOT](https://graphviz.org/doc/info/lang.html)
+//! language, optionally annotated with the facts from a solve.
+//!
+//! Nodes are named after their position in `Graph::get_all_node_ids`, so
+//! `NodeId`s don't need to implement anything beyond what `Node` requires. All
+//! text is provided by the caller through formatting closures.
+//!
+//! ```ignore
+//! let res = analyzer.solve(&graph);
+//! let dot = creek::dot::render_facts(&graph, |b| format!("{:?}", b.id), &res, |f| {
+//!     format!("{:?}", f.live)
+//! });
+//! std::fs::write("live.dot", dot)?;
+//! ```
+
+use std::fmt::Write;
+
+use super::HashMap;
+use super::{Fact, Graph, Node, NodeInfo};
+
+/// Render `graph` with only the node labels
+pub fn render<N, G, NodeFmt>(graph: &G, node_fmt: NodeFmt) -> String
+where
+    N: Node,
+    G: Graph<N>,
+    NodeFmt: Fn(&N) -> String,
+{
+    write_graph(graph, |_, node| (node_fmt(node), false))
+}
+
+/// Render `graph`, annotating every node with its `before` and `after` facts
+/// from `facts`. Nodes without facts are only given their label.
+pub fn render_facts<F, N, G, NodeFmt, FactFmt>(
+    graph: &G,
+    node_fmt: NodeFmt,
+    facts: &HashMap<N::NodeId, NodeInfo<F>>,
+    fact_fmt: FactFmt,
+) -> String
+where
+    F: Fact,
+    N: Node,
+    G: Graph<N>,
+    NodeFmt: Fn(&N) -> String,
+    FactFmt: Fn(&F) -> String,
+{
+    write_graph(graph, |id, node| {
+        let label = annotate(node_fmt(node), facts.get(&id), &fact_fmt);
+        (label, false)
+    })
+}
+
+/// Render `graph` annotated with the facts in `new`, highlighting every node
+/// whose facts differ from those in `old`. A node which only appears in one of
+/// the solutions counts as changed.
+pub fn render_changes<F, N, G, NodeFmt, FactFmt>(
+    graph: &G,
+    node_fmt: NodeFmt,
+    old: &HashMap<N::NodeId, NodeInfo<F>>,
+    new: &HashMap<N::NodeId, NodeInfo<F>>,
+    fact_fmt: FactFmt,
+) -> String
+where
+    F: Fact,
+    N: Node,
+    G: Graph<N>,
+    NodeFmt: Fn(&N) -> String,
+    FactFmt: Fn(&F) -> String,
+{
+    write_graph(graph, |id, node| {
+        let info = new.get(&id);
+        let changed = info != old.get(&id);
+        (annotate(node_fmt(node), info, &fact_fmt), changed)
+    })
+}
+
+/// Add the facts in `info` below `label`
+fn annotate<F, FactFmt>(label: String, info: Option<&NodeInfo<F>>, fact_fmt: &FactFmt) -> String
+where
+    F: Fact,
+    FactFmt: Fn(&F) -> String,
+{
+    match info {
+        Some(info) => format!(
+            "{}\nbefore: {}\nafter: {}",
+            label,
+            fact_fmt(&info.before),
+            fact_fmt(&info.after)
+        ),
+        None => label,
+    }
+}
+
+/// Write the whole graph. `describe` gives the label for a node and whether it
+/// should be highlighted.
+fn write_graph<N, G, Describe>(graph: &G, mut describe: Describe) -> String
+where
+    N: Node,
+    G: Graph<N>,
+    Describe: FnMut(N::NodeId, &N) -> (String, bool),
+{
+    let ids = graph.get_all_node_ids();
+    let names: HashMap<N::NodeId, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+
+    let entry = graph.get_entry();
+    let exit = graph.get_exit();
+
+    // Writing to a `String` can't fail, so the results are ignored
+    let mut out = String::new();
+    let _ = writeln!(out, "digraph {{");
+    let _ = writeln!(out, "    node [shape=box, fontname=monospace];");
+
+    for (i, id) in ids.iter().enumerate() {
+        let (label, highlight) = describe(*id, graph.get(*id));
+        let _ = write!(out, "    n{} [label=\"{}\"", i, escape(&label));
+
+        if *id == entry || *id == exit {
+            let _ = write!(out, ", peripheries=2");
+        }
+
+        if highlight {
+            let _ = write!(out, ", style=filled, fillcolor=\"#ffd7d7\", color=red");
+        }
+
+        let _ = writeln!(out, "];");
+    }
+
+    for (i, id) in ids.iter().enumerate() {
+        for succ in graph.get_succs(*id) {
+            if let Some(j) = names.get(succ) {
+                let _ = writeln!(out, "    n{} -> n{};", i, j);
+            }
+        }
+    }
+
+    let _ = writeln!(out, "}}");
+    out
+}
+
+/// Escape a label so it can be put in a quoted string. Lines are
+/// left-justified.
+fn escape(label: &str) -> String {
+    let mut res = String::with_capacity(label.len());
+
+    for c in label.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\l"),
+            c => res.push(c),
+        }
+    }
+
+    res.push_str("\\l");
+    res
+}
@@ -0,0 +1,255 @@
+//! Random control flow graphs, for fuzzing and stress testing analyses.
+//!
+//! Graphs are built from a seeded `Rng`, so a failing graph can always be made
+//! again from its seed. Blocks are numbered from 1 in the order they're
+//! generated, the first block is the entry and the last reachable one is the
+//! exit:
+//!
+//! ```plain
+//! 1. Every block after the first gets an edge from an earlier block, so every
+//!    block is reachable from the entry.
+//! 2. Blocks get extra forward edges to later blocks, for branches.
+//! 3. Blocks get back edges to a block which dominates them, for loops.
+//! 4. Blocks get back edges to an earlier block which doesn't dominate them,
+//!    but can reach them, for irreducible loops.
+//! 5. Unreachable blocks get edges into the rest of the graph.
+//! ```
+//!
+//! Besides the exit, some blocks may have no successors, like a `return` in
+//! the middle of a function.
+
+use super::cfg::{BinOp, Block, BlockId, Expr, NodeGraph, Statement, Variable};
+use super::{Graph, HashSet};
+
+/// A small seeded random number generator (xorshift64*)
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    /// Create a generator from a seed. Equal seeds give equal numbers.
+    pub fn new(seed: u64) -> Self {
+        // Spread the seed out, since xorshift can't start from 0
+        Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    /// Get the next random number
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Get a random number below `n`, which must be positive
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Return `true` with probability `p`
+    pub fn chance(&mut self, p: f64) -> bool {
+        let unit = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        unit < p
+    }
+}
+
+/// The shape of the graphs to generate
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    /// The number of blocks reachable from the entry, at least 1
+    pub blocks: usize,
+
+    /// The chance for each block to get an extra edge forward
+    pub branches: f64,
+
+    /// The chance for each block to get a back edge to one of its dominators,
+    /// making a loop
+    pub loops: f64,
+
+    /// The chance for each block to get a back edge to an earlier block which
+    /// can reach it but doesn't dominate it, making a loop with more than one
+    /// entry. Blocks which no such block can reach don't get one.
+    pub irreducible: f64,
+
+    /// The number of extra blocks which can't be reached from the entry
+    pub unreachable: usize,
+
+    /// The number of blocks without successors, including the exit
+    pub exits: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            blocks: 10,
+            branches: 0.3,
+            loops: 0.2,
+            irreducible: 0.0,
+            unreachable: 0,
+            exits: 1,
+        }
+    }
+}
+
+/// Generate a graph shaped by `config`, with the data of each block given by
+/// `data`
+pub fn graph<T, Data>(config: &Config, rng: &mut Rng, mut data: Data) -> NodeGraph<T>
+where
+    Data: FnMut(&mut Rng, BlockId) -> T,
+{
+    let n = config.blocks.max(1);
+
+    // Pick the blocks in the middle which end early. The entry can't, or
+    // nothing else would be reachable, and the last block is the exit.
+    let mut sinks = HashSet::default();
+    for _ in 1..config.exits.min(n.saturating_sub(1)) {
+        let free: Vec<_> = (2..n).filter(|i| !sinks.contains(i)).collect();
+        sinks.insert(free[rng.below(free.len())]);
+    }
+
+    let mut graph = NodeGraph::new(Block::new(BlockId(1), data(rng, BlockId(1))));
+    for i in 2..=n {
+        graph.insert(Block::new(BlockId(i), data(rng, BlockId(i))));
+    }
+
+    if n > 1 {
+        graph.set_exit(BlockId(n));
+    }
+
+    // Every block gets an edge from an earlier one, and every block which
+    // doesn't end early gets an edge forward, and maybe another
+    for i in 2..=n {
+        let from: Vec<_> = (1..i).filter(|j| !sinks.contains(j)).collect();
+        let j = from[rng.below(from.len())];
+        graph.add_edge(BlockId(j), BlockId(i));
+    }
+
+    for i in 1..n {
+        let dead_end = graph.get_succs(BlockId(i)).is_empty();
+        if !sinks.contains(&i) && (dead_end || rng.chance(config.branches)) {
+            let to = i + 1 + rng.below(n - i);
+            graph.add_edge(BlockId(i), BlockId(to));
+        }
+    }
+
+    // Every edge goes forward so far, so handling blocks in order means the
+    // dominators of every predecessor are known. Back edges to dominators
+    // don't change them.
+    let mut idom = vec![0; n + 1];
+    idom[1] = 1;
+
+    for i in 2..=n {
+        idom[i] = graph
+            .get_preds(BlockId(i))
+            .iter()
+            .map(|p| p.0)
+            .fold(0, |a, b| match a {
+                0 => b,
+                a => intersect(&idom, a, b),
+            });
+    }
+
+    let sources: Vec<_> = (1..=n)
+        .filter(|i| !sinks.contains(i) && (*i < n || n == 1))
+        .collect();
+
+    // Back edges to a dominator, which all go in before any edge that could
+    // change the dominators
+    for i in sources.iter().copied() {
+        if rng.chance(config.loops) {
+            let doms = dominators(&idom, i);
+            let to = doms[rng.below(doms.len())];
+            graph.add_edge(BlockId(i), BlockId(to));
+        }
+    }
+
+    // Back edges to any other earlier block which can reach `i`, so the edge
+    // closes a loop. These can take dominators away, but never add any, so a
+    // block which didn't dominate `i` still doesn't.
+    for i in sources.iter().copied() {
+        if rng.chance(config.irreducible) {
+            let doms = dominators(&idom, i);
+            let reach = reaching(&graph, i);
+            let others: Vec<_> = (1..i)
+                .filter(|j| !doms.contains(j) && reach.contains(j))
+                .collect();
+            if !others.is_empty() {
+                let to = others[rng.below(others.len())];
+                graph.add_edge(BlockId(i), BlockId(to));
+            }
+        }
+    }
+
+    // Unreachable blocks lead into the graph, or into each other
+    for i in n + 1..=n + config.unreachable {
+        graph.insert(Block::new(BlockId(i), data(rng, BlockId(i))));
+
+        let to = 1 + rng.below(i - 1);
+        graph.add_edge(BlockId(i), BlockId(to));
+    }
+
+    graph
+}
+
+/// Generate `len` random statements over the variables `0..vars`, which must
+/// be positive
+pub fn statements(rng: &mut Rng, vars: usize, len: usize) -> Vec<Statement> {
+    let var = |rng: &mut Rng| Variable(rng.below(vars));
+
+    (0..len)
+        .map(|_| match rng.below(4) {
+            0 => Statement::Declare(var(rng)),
+            1 => Statement::ConstAssign(var(rng), rng.below(10) as i32),
+            2 => Statement::VarAssign(var(rng), var(rng)),
+            _ => {
+                let op = [BinOp::Add, BinOp::Sub, BinOp::Mul][rng.below(3)];
+                let expr = Expr {
+                    op,
+                    args: [var(rng), var(rng)],
+                };
+
+                Statement::ExprAssign(var(rng), expr)
+            }
+        })
+        .collect()
+}
+
+/// Find the nearest common dominator of `a` and `b`, given the immediate
+/// dominators of blocks numbered so that dominators come first
+fn intersect(idom: &[usize], mut a: usize, mut b: usize) -> usize {
+    while a != b {
+        while a > b {
+            a = idom[a];
+        }
+
+        while b > a {
+            b = idom[b];
+        }
+    }
+
+    a
+}
+
+/// Get every block which can reach `i`, including itself
+fn reaching<T>(graph: &NodeGraph<T>, i: usize) -> HashSet<usize> {
+    let mut seen = HashSet::default();
+    let mut stack = vec![i];
+
+    while let Some(j) = stack.pop() {
+        if seen.insert(j) {
+            stack.extend(graph.get_preds(BlockId(j)).iter().map(|p| p.0));
+        }
+    }
+
+    seen
+}
+
+/// Get every block which dominates `i`, including itself
+fn dominators(idom: &[usize], mut i: usize) -> Vec<usize> {
+    let mut doms = vec![i];
+    while idom[i] != i {
+        i = idom[i];
+        doms.push(i);
+    }
+
+    doms
+}
@@ -1,5 +1,11 @@
 #[cfg(feature = "fnv")]
 pub type HashMap<K, V> = fnv::FnvHashMap<K, V>;
 
+#[cfg(feature = "fnv")]
+pub type HashSet<T> = fnv::FnvHashSet<T>;
+
 #[cfg(not(feature = "fnv"))]
 pub type HashMap<K, V> = std::collections::HashMap<K, V>;
+
+#[cfg(not(feature = "fnv"))]
+pub type HashSet<T> = std::collections::HashSet<T>;
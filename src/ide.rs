@@ -0,0 +1,486 @@
+//! An IDE solver, which extends IFDS so that every fact carries a value, such
+//! as the constant held by a variable. Follows Sagiv, Reps and Horwitz.
+//!
+//! The facts flow exactly like in `ifds`, but every edge of the exploded
+//! supergraph, from a fact `d` to a fact `d'`, also has an edge function which
+//! gives the value of `d'` from the value of `d`. The solver works in two
+//! phases:
+//!
+//! ```plain
+//! 1. Find jump functions, from the value of each fact at the entry of a
+//!    function to the value of each fact at every point in it, composing and
+//!    joining edge functions along the way, and summarizing calls.
+//! 2. Find the value of every fact at the entry of every function, starting
+//!    from `top` for `zero` at the entry of the program, and then apply the
+//!    jump functions to those.
+//! ```
+//!
+//! Edge functions are pluggable through `EdgeFn`, and `Linear` implements the
+//! functions `x -> a * x + b` used by linear constant propagation.
+
+use std::collections::VecDeque;
+use std::hash::Hash;
+
+use super::ifds::Flows;
+use super::interproc::{self, Program};
+use super::lattice::{Bounded, Flat, Lattice, MapLattice};
+use super::{Graph, HashMap, HashSet, Node, NodeInfo};
+
+/// A function from values to values, which labels an edge of the exploded
+/// supergraph. Edge functions need to form a lattice of finite height for the
+/// solve to terminate.
+pub trait EdgeFn<V>: Clone + PartialEq {
+    /// The function which gives back its argument
+    fn identity() -> Self;
+
+    /// The function which gives nothing, for edges which haven't been found
+    /// yet. This is the identity of `join`.
+    fn bottom() -> Self;
+
+    /// Apply the function to a value
+    fn apply(&self, value: &V) -> V;
+
+    /// Compose two functions, so that `self` is applied first and `next` is
+    /// applied to its result
+    fn then(&self, next: &Self) -> Self;
+
+    /// A function which gives at least as much as both functions, for every
+    /// value
+    fn join(&self, other: &Self) -> Self;
+}
+
+/// The edge functions of an IDE problem, given for every edge that the flow
+/// functions produce. Each method gets the fact the edge starts at, `fact`,
+/// and the fact it ends at, `next`.
+pub trait Edges<N: Node, P: Program<N>>: Flows<N, P> {
+    /// The value carried by every fact
+    type Value: Bounded;
+
+    /// The functions labeling edges
+    type EdgeFn: EdgeFn<Self::Value>;
+
+    /// The function for an edge given by `Flows::normal`
+    fn normal_edge(
+        &self,
+        func: P::FuncId,
+        node: &N,
+        fact: &Self::Fact,
+        next: &Self::Fact,
+    ) -> Self::EdgeFn;
+
+    /// The function for an edge given by `Flows::call`
+    fn call_edge(
+        &self,
+        func: P::FuncId,
+        node: &N,
+        callee: P::FuncId,
+        fact: &Self::Fact,
+        next: &Self::Fact,
+    ) -> Self::EdgeFn;
+
+    /// The function for an edge given by `Flows::ret`
+    fn ret_edge(
+        &self,
+        func: P::FuncId,
+        node: &N,
+        callee: P::FuncId,
+        fact: &Self::Fact,
+        next: &Self::Fact,
+    ) -> Self::EdgeFn;
+
+    /// The function for an edge given by `Flows::call_to_return`
+    fn call_to_return_edge(
+        &self,
+        func: P::FuncId,
+        node: &N,
+        fact: &Self::Fact,
+        next: &Self::Fact,
+    ) -> Self::EdgeFn;
+}
+
+/// The edge functions of linear constant propagation, over constant values
+///
+/// ```plain
+/// Bottom:            x -> Bottom
+/// Affine { a, b }:   x -> a * x + b
+/// Top:               x -> Top
+/// ```
+///
+/// Arithmetic wraps on overflow.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Linear {
+    Bottom,
+    Affine { a: i64, b: i64 },
+    Top,
+}
+
+impl Linear {
+    /// The function which always gives `b`
+    pub fn constant(b: i64) -> Self {
+        Linear::Affine { a: 0, b }
+    }
+}
+
+impl EdgeFn<Flat<i64>> for Linear {
+    fn identity() -> Self {
+        Linear::Affine { a: 1, b: 0 }
+    }
+
+    fn bottom() -> Self {
+        Linear::Bottom
+    }
+
+    fn apply(&self, value: &Flat<i64>) -> Flat<i64> {
+        match (self, value) {
+            (Linear::Bottom, _) => Flat::Bottom,
+            (Linear::Top, _) => Flat::Top,
+            (Linear::Affine { a: 0, b }, _) => Flat::Const(*b),
+            (Linear::Affine { a, b }, Flat::Const(x)) => {
+                Flat::Const(a.wrapping_mul(*x).wrapping_add(*b))
+            }
+            (Linear::Affine { .. }, value) => *value,
+        }
+    }
+
+    fn then(&self, next: &Self) -> Self {
+        match (self, next) {
+            (Linear::Bottom, _) | (_, Linear::Bottom) => Linear::Bottom,
+            (_, Linear::Affine { a: 0, b }) => Linear::constant(*b),
+            (_, Linear::Top) | (Linear::Top, _) => Linear::Top,
+            (Linear::Affine { a, b }, Linear::Affine { a: c, b: d }) => Linear::Affine {
+                a: c.wrapping_mul(*a),
+                b: c.wrapping_mul(*b).wrapping_add(*d),
+            },
+        }
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Linear::Bottom, f) | (f, Linear::Bottom) => *f,
+            (f, g) if f == g => *f,
+            _ => Linear::Top,
+        }
+    }
+}
+
+/// A point in a function, either before or after a node runs
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum Point<Id> {
+    In(Id),
+    Out(Id),
+}
+
+/// The values found by the solver
+pub struct Solution<N, P, E>
+where
+    N: Node,
+    P: Program<N>,
+    E: Edges<N, P>,
+{
+    /// The value of every fact which may hold before and after every node of
+    /// every function reached from the entry function, not including `zero`
+    pub values: Values<P::FuncId, N::NodeId, E::Fact, E::Value>,
+
+    /// The function from each fact at the entry of each function to each fact
+    /// at its exit, for each fact it was reached with
+    pub summaries: Summaries<P::FuncId, E::Fact, E::EdgeFn>,
+}
+
+/// The value of every fact before and after every node of every function
+type Values<FuncId, NodeId, D, V> = HashMap<FuncId, HashMap<NodeId, NodeInfo<MapLattice<D, V>>>>;
+
+/// A path edge, from a fact at the entry of a function to a fact at a point in
+/// it
+type PathEdge<FuncId, NodeId, D> = (FuncId, D, Point<NodeId>, D);
+
+/// The jump function of every path edge found so far
+type Jumps<FuncId, NodeId, D, E> = HashMap<PathEdge<FuncId, NodeId, D>, E>;
+
+/// The calls which reached each function with each entry fact, as the caller,
+/// the call node, the fact at the caller's entry, and the fact at the call
+type Incoming<FuncId, NodeId, D> = HashMap<(FuncId, D), HashSet<(FuncId, NodeId, D, D)>>;
+
+/// The function from each fact at the entry of each function to each fact at
+/// its exit
+type Summaries<FuncId, D, E> = HashMap<(FuncId, D), HashMap<D, E>>;
+
+struct Solver<'a, N, P, E>
+where
+    N: Node,
+    P: Program<N>,
+    E: Edges<N, P>,
+{
+    program: &'a P,
+    edges: &'a E,
+
+    jumps: Jumps<P::FuncId, N::NodeId, E::Fact, E::EdgeFn>,
+    worklist: VecDeque<PathEdge<P::FuncId, N::NodeId, E::Fact>>,
+    incoming: Incoming<P::FuncId, N::NodeId, E::Fact>,
+    summaries: Summaries<P::FuncId, E::Fact, E::EdgeFn>,
+}
+
+impl<'a, N, P, E> Solver<'a, N, P, E>
+where
+    N: Node,
+    P: Program<N>,
+    E: Edges<N, P>,
+{
+    /// Join `f` into the jump function of `edge`
+    fn propagate(&mut self, edge: PathEdge<P::FuncId, N::NodeId, E::Fact>, f: E::EdgeFn) {
+        let old = self
+            .jumps
+            .get(&edge)
+            .cloned()
+            .unwrap_or_else(E::EdgeFn::bottom);
+        let new = old.join(&f);
+
+        if new != old {
+            self.jumps.insert(edge.clone(), new);
+            if !self.worklist.contains(&edge) {
+                self.worklist.push_back(edge);
+            }
+        }
+    }
+
+    /// Phase 1: find the jump functions
+    fn run(&mut self) {
+        while let Some(edge) = self.worklist.pop_front() {
+            let f = self.jumps[&edge].clone();
+            let (func, d1, point, d2) = edge;
+            let graph = self.program.graph(func);
+
+            match point {
+                Point::In(id) => {
+                    let node = graph.get(id);
+                    let callees = self.program.callees(func, node);
+
+                    if callees.is_empty() {
+                        for d3 in self.edges.normal(func, node, &d2) {
+                            let g = self.edges.normal_edge(func, node, &d2, &d3);
+                            self.propagate((func, d1.clone(), Point::Out(id), d3), f.then(&g));
+                        }
+
+                        continue;
+                    }
+
+                    for callee in callees {
+                        self.enter(func, id, *callee, &d1, &d2, &f);
+                    }
+
+                    for d3 in self.edges.call_to_return(func, node, &d2) {
+                        let g = self.edges.call_to_return_edge(func, node, &d2, &d3);
+                        self.propagate((func, d1.clone(), Point::Out(id), d3), f.then(&g));
+                    }
+                }
+                Point::Out(id) => {
+                    if id == graph.get_exit() {
+                        self.exit(func, &d1, &d2, &f);
+                    }
+
+                    for succ in interproc::nexts(self.program, func, id) {
+                        self.propagate((func, d1.clone(), Point::In(succ), d2.clone()), f.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Follow a call from `id` in `func` into `callee`, where `f` is the jump
+    /// function from `d1` to `d2` before the call
+    fn enter(
+        &mut self,
+        func: P::FuncId,
+        id: N::NodeId,
+        callee: P::FuncId,
+        d1: &E::Fact,
+        d2: &E::Fact,
+        f: &E::EdgeFn,
+    ) {
+        let node = self.program.graph(func).get(id);
+        let entry = self.program.graph(callee).get_entry();
+
+        for d3 in self.edges.call(func, node, callee, d2) {
+            let start = (callee, d3.clone(), Point::In(entry), d3.clone());
+            self.propagate(start, E::EdgeFn::identity());
+
+            let key = (callee, d3.clone());
+            self.incoming.entry(key.clone()).or_default().insert((
+                func,
+                id,
+                d1.clone(),
+                d2.clone(),
+            ));
+
+            // Apply what the callee is already known to do
+            let call = f.then(&self.edges.call_edge(func, node, callee, d2, &d3));
+            let exits: Vec<_> = match self.summaries.get(&key) {
+                Some(exits) => exits.iter().map(|(d, s)| (d.clone(), s.clone())).collect(),
+                None => continue,
+            };
+
+            for (d4, summary) in exits {
+                for d5 in self.edges.ret(func, node, callee, &d4) {
+                    let ret = self.edges.ret_edge(func, node, callee, &d4, &d5);
+                    let h = call.then(&summary).then(&ret);
+                    self.propagate((func, d1.clone(), Point::Out(id), d5), h);
+                }
+            }
+        }
+    }
+
+    /// Record that `func` reached with `d1` produces `d2` at its exit through
+    /// `f`, and return it to every call which reached `func` with `d1`
+    fn exit(&mut self, func: P::FuncId, d1: &E::Fact, d2: &E::Fact, f: &E::EdgeFn) {
+        let key = (func, d1.clone());
+        let exits = self.summaries.entry(key.clone()).or_default();
+        let old = exits.get(d2).cloned().unwrap_or_else(E::EdgeFn::bottom);
+        let new = old.join(f);
+
+        if new == old {
+            return;
+        }
+
+        exits.insert(d2.clone(), new.clone());
+
+        let calls: Vec<_> = self
+            .incoming
+            .get(&key)
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect();
+        for (caller, id, c1, c2) in calls {
+            let node = self.program.graph(caller).get(id);
+            let jump = self.jumps[&(caller, c1.clone(), Point::In(id), c2.clone())].clone();
+            let call = jump.then(&self.edges.call_edge(caller, node, func, &c2, d1));
+
+            for d5 in self.edges.ret(caller, node, func, d2) {
+                let ret = self.edges.ret_edge(caller, node, func, d2, &d5);
+                let h = call.then(&new).then(&ret);
+                self.propagate((caller, c1.clone(), Point::Out(id), d5), h);
+            }
+        }
+    }
+
+    /// Phase 2: find the value of every fact at the entry of every function
+    fn entry_values(
+        &self,
+        entry: P::FuncId,
+        zero: E::Fact,
+    ) -> HashMap<(P::FuncId, E::Fact), E::Value> {
+        // The jump functions to every call, by the function and the fact at
+        // its entry they start from
+        let mut calls: HashMap<_, Vec<_>> = HashMap::default();
+        for ((func, d1, point, d2), jump) in self.jumps.iter() {
+            if let Point::In(id) = point {
+                let node = self.program.graph(*func).get(*id);
+                if !self.program.callees(*func, node).is_empty() {
+                    let start = (*func, d1.clone());
+                    calls.entry(start).or_default().push((*id, d2, jump));
+                }
+            }
+        }
+
+        let mut values = HashMap::default();
+        values.insert((entry, zero.clone()), E::Value::top());
+
+        let mut worklist = VecDeque::new();
+        worklist.push_back((entry, zero));
+
+        while let Some((func, d1)) = worklist.pop_front() {
+            let value = values[&(func, d1.clone())].clone();
+
+            // Follow every call made with `d1` at the entry of `func`
+            for (id, c2, jump) in calls.get(&(func, d1)).into_iter().flatten() {
+                let node = self.program.graph(func).get(*id);
+                for callee in self.program.callees(func, node) {
+                    let at_call = jump.apply(&value);
+
+                    for d3 in self.edges.call(func, node, *callee, c2) {
+                        let g = self.edges.call_edge(func, node, *callee, c2, &d3);
+                        let key = (*callee, d3);
+                        let old = values.get(&key).cloned().unwrap_or_else(E::Value::bottom);
+                        let new = old.join(&g.apply(&at_call));
+
+                        if new != old {
+                            values.insert(key.clone(), new);
+                            worklist.push_back(key);
+                        }
+                    }
+                }
+            }
+        }
+
+        values
+    }
+}
+
+/// Solve an IDE problem over `program`, starting from `entry` with only the
+/// `zero` fact, whose value is `top`
+pub fn solve<N, P, E>(program: &P, edges: &E, entry: P::FuncId) -> Solution<N, P, E>
+where
+    N: Node,
+    P: Program<N>,
+    E: Edges<N, P>,
+{
+    let mut solver = Solver {
+        program,
+        edges,
+        jumps: HashMap::default(),
+        worklist: VecDeque::new(),
+        incoming: HashMap::default(),
+        summaries: HashMap::default(),
+    };
+
+    let zero = edges.zero();
+    let start = program.graph(entry).get_entry();
+    solver.propagate(
+        (entry, zero.clone(), Point::In(start), zero.clone()),
+        E::EdgeFn::identity(),
+    );
+    solver.run();
+
+    let entries = solver.entry_values(entry, zero.clone());
+
+    // Every function reached gets values for all of its nodes, even the ones
+    // no fact reaches
+    let mut values: Values<_, _, _, E::Value> = HashMap::default();
+    for (func, _, _, _) in solver.jumps.keys() {
+        values.entry(*func).or_insert_with(|| {
+            let graph = program.graph(*func);
+            let empty = NodeInfo {
+                before: MapLattice::bottom(),
+                after: MapLattice::bottom(),
+            };
+
+            graph
+                .get_all_node_ids()
+                .iter()
+                .map(|id| (*id, empty.clone()))
+                .collect()
+        });
+    }
+
+    for ((func, d1, point, d2), jump) in solver.jumps.iter() {
+        let value = match entries.get(&(*func, d1.clone())) {
+            Some(value) if *d2 != zero => jump.apply(value),
+            _ => continue,
+        };
+
+        let info = values.get_mut(func).unwrap();
+        let facts = match point {
+            Point::In(id) => &mut info.get_mut(id).unwrap().before,
+            Point::Out(id) => &mut info.get_mut(id).unwrap().after,
+        };
+
+        let joined = facts.get(d2).join(&value);
+        facts.insert(d2.clone(), joined);
+    }
+
+    let mut summaries = solver.summaries;
+    for exits in summaries.values_mut() {
+        exits.remove(&zero);
+    }
+
+    Solution { values, summaries }
+}
@@ -0,0 +1,305 @@
+//! An IFDS solver, for precise interprocedural analysis of distributive
+//! problems over a finite set of facts, following Reps, Horwitz and Sagiv's
+//! tabulation algorithm.
+//!
+//! Each node in each function of a `Program` has an `in` point, before it runs,
+//! and an `out` point, after it runs. Facts flow from the `out` point of a node
+//! to the `in` point of its successors unchanged, and through nodes by the
+//! flow functions of a `Flows`:
+//!
+//! ```plain
+//! normal:         in(n) -> out(n)               n isn't a call
+//! call:           in(n) -> in(entry(callee))    n calls callee
+//! ret:            out(exit(callee)) -> out(n)   n calls callee
+//! call_to_return: in(n) -> out(n)               n calls something
+//! ```
+//!
+//! A flow function maps a single fact to the facts it produces, so the
+//! problem has to be distributive. The special `zero` fact always holds, and
+//! new facts are generated from it.
+//!
+//! The solver builds path edges, which record that a fact at a function's
+//! entry leads to a fact at some point in that function, and end summaries,
+//! which record the facts a function produces at its exit for each fact at its
+//! entry. Summaries are reused at every call with the same fact, which keeps
+//! the solve context sensitive: facts only return to the call sites they came
+//! from.
+
+use std::collections::VecDeque;
+use std::hash::Hash;
+
+use super::interproc::{self, Program};
+use super::lattice::PowerSet;
+use super::{Graph, HashMap, HashSet, Node, NodeInfo};
+
+/// The flow functions of an IFDS problem
+pub trait Flows<N: Node, P: Program<N>> {
+    /// A single fact, from a finite set
+    type Fact: Clone + Eq + Hash;
+
+    /// The fact which always holds
+    fn zero(&self) -> Self::Fact;
+
+    /// The facts after `node`, which isn't a call, given `fact` before it
+    fn normal(&self, func: P::FuncId, node: &N, fact: &Self::Fact) -> Vec<Self::Fact>;
+
+    /// The facts at the entry of `callee`, given `fact` before the call `node`
+    fn call(
+        &self,
+        func: P::FuncId,
+        node: &N,
+        callee: P::FuncId,
+        fact: &Self::Fact,
+    ) -> Vec<Self::Fact>;
+
+    /// The facts after the call `node`, given `fact` at the exit of `callee`
+    fn ret(
+        &self,
+        func: P::FuncId,
+        node: &N,
+        callee: P::FuncId,
+        fact: &Self::Fact,
+    ) -> Vec<Self::Fact>;
+
+    /// The facts after the call `node` which don't go through the callee,
+    /// given `fact` before it, such as facts about local variables
+    fn call_to_return(&self, func: P::FuncId, node: &N, fact: &Self::Fact) -> Vec<Self::Fact>;
+}
+
+/// A flow function for gen/kill problems, like reaching definitions. `zero`
+/// generates `gen`, and every other fact survives unless `kills` is true
+/// for it.
+pub fn gen_kill<D, Gen, Kills>(fact: &D, zero: &D, gen: Gen, kills: Kills) -> Vec<D>
+where
+    D: Clone + Eq,
+    Gen: IntoIterator<Item = D>,
+    Kills: FnOnce(&D) -> bool,
+{
+    if fact == zero {
+        std::iter::once(zero.clone()).chain(gen).collect()
+    } else if kills(fact) {
+        Vec::new()
+    } else {
+        vec![fact.clone()]
+    }
+}
+
+/// A point in a function, either before or after a node runs
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum Point<Id> {
+    In(Id),
+    Out(Id),
+}
+
+/// The facts found by the solver
+pub struct Solution<FuncId: Eq + Hash, NodeId: Eq + Hash, D: Clone + Eq + Hash> {
+    /// The facts which may hold before and after every node of every function
+    /// reached from the entry function, not including `zero`
+    pub facts: HashMap<FuncId, HashMap<NodeId, NodeInfo<PowerSet<D>>>>,
+
+    /// The facts each function may produce at its exit, for each fact at its
+    /// entry it was reached with
+    pub summaries: Summaries<FuncId, D>,
+}
+
+/// A path edge, from a fact at the entry of a function to a fact at a point in
+/// it
+type PathEdge<FuncId, NodeId, D> = (FuncId, D, Point<NodeId>, D);
+
+/// The calls which reached each function with each entry fact, as the caller,
+/// the call node, and the fact at the caller's entry
+type Incoming<FuncId, NodeId, D> = HashMap<(FuncId, D), Vec<(FuncId, NodeId, D)>>;
+
+/// The facts each function produces at its exit, for each fact at its entry
+type Summaries<FuncId, D> = HashMap<(FuncId, D), HashSet<D>>;
+
+struct Solver<'a, N, P, Fl>
+where
+    N: Node,
+    P: Program<N>,
+    Fl: Flows<N, P>,
+{
+    program: &'a P,
+    flows: &'a Fl,
+
+    path_edges: HashSet<PathEdge<P::FuncId, N::NodeId, Fl::Fact>>,
+    worklist: VecDeque<PathEdge<P::FuncId, N::NodeId, Fl::Fact>>,
+    incoming: Incoming<P::FuncId, N::NodeId, Fl::Fact>,
+    summaries: Summaries<P::FuncId, Fl::Fact>,
+}
+
+impl<'a, N, P, Fl> Solver<'a, N, P, Fl>
+where
+    N: Node,
+    P: Program<N>,
+    Fl: Flows<N, P>,
+{
+    fn propagate(&mut self, edge: PathEdge<P::FuncId, N::NodeId, Fl::Fact>) {
+        if !self.path_edges.contains(&edge) {
+            self.path_edges.insert(edge.clone());
+            self.worklist.push_back(edge);
+        }
+    }
+
+    fn run(&mut self) {
+        while let Some((func, d1, point, d2)) = self.worklist.pop_front() {
+            let graph = self.program.graph(func);
+
+            match point {
+                Point::In(id) => {
+                    let node = graph.get(id);
+                    let callees = self.program.callees(func, node);
+
+                    if callees.is_empty() {
+                        for d3 in self.flows.normal(func, node, &d2) {
+                            self.propagate((func, d1.clone(), Point::Out(id), d3));
+                        }
+
+                        continue;
+                    }
+
+                    for callee in callees {
+                        self.enter(func, id, *callee, &d1, &d2);
+                    }
+
+                    for d3 in self.flows.call_to_return(func, node, &d2) {
+                        self.propagate((func, d1.clone(), Point::Out(id), d3));
+                    }
+                }
+                Point::Out(id) => {
+                    if id == graph.get_exit() {
+                        self.exit(func, &d1, &d2);
+                    }
+
+                    for succ in interproc::nexts(self.program, func, id) {
+                        self.propagate((func, d1.clone(), Point::In(succ), d2.clone()));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Follow a call from `node` in `func` into `callee`, with the path edge
+    /// from `d1` to `d2` before the call
+    fn enter(
+        &mut self,
+        func: P::FuncId,
+        id: N::NodeId,
+        callee: P::FuncId,
+        d1: &Fl::Fact,
+        d2: &Fl::Fact,
+    ) {
+        let node = self.program.graph(func).get(id);
+        let entry = self.program.graph(callee).get_entry();
+
+        for d3 in self.flows.call(func, node, callee, d2) {
+            self.propagate((callee, d3.clone(), Point::In(entry), d3.clone()));
+
+            let key = (callee, d3);
+            self.incoming
+                .entry(key.clone())
+                .or_default()
+                .push((func, id, d1.clone()));
+
+            // Apply what the callee is already known to do
+            let exits: Vec<_> = match self.summaries.get(&key) {
+                Some(exits) => exits.iter().cloned().collect(),
+                None => continue,
+            };
+
+            for d4 in exits {
+                for d5 in self.flows.ret(func, node, callee, &d4) {
+                    self.propagate((func, d1.clone(), Point::Out(id), d5));
+                }
+            }
+        }
+    }
+
+    /// Record that `func` reached with `d1` produces `d2` at its exit, and
+    /// return it to every call which reached `func` with `d1`
+    fn exit(&mut self, func: P::FuncId, d1: &Fl::Fact, d2: &Fl::Fact) {
+        let key = (func, d1.clone());
+        if !self
+            .summaries
+            .entry(key.clone())
+            .or_default()
+            .insert(d2.clone())
+        {
+            return;
+        }
+
+        let calls = self.incoming.get(&key).cloned().unwrap_or_default();
+        for (caller, id, c1) in calls {
+            let node = self.program.graph(caller).get(id);
+
+            for d5 in self.flows.ret(caller, node, func, d2) {
+                self.propagate((caller, c1.clone(), Point::Out(id), d5));
+            }
+        }
+    }
+}
+
+/// Solve an IFDS problem over `program`, starting from `entry` with only the
+/// `zero` fact
+pub fn solve<N, P, Fl>(
+    program: &P,
+    flows: &Fl,
+    entry: P::FuncId,
+) -> Solution<P::FuncId, N::NodeId, Fl::Fact>
+where
+    N: Node,
+    P: Program<N>,
+    Fl: Flows<N, P>,
+{
+    let mut solver = Solver {
+        program,
+        flows,
+        path_edges: HashSet::default(),
+        worklist: VecDeque::new(),
+        incoming: HashMap::default(),
+        summaries: HashMap::default(),
+    };
+
+    let zero = flows.zero();
+    let start = program.graph(entry).get_entry();
+    solver.propagate((entry, zero.clone(), Point::In(start), zero.clone()));
+    solver.run();
+
+    // Every function reached gets facts for all of its nodes, even the ones
+    // no fact reaches
+    let mut facts: HashMap<_, HashMap<_, _>> = HashMap::default();
+    for (func, _, _, _) in solver.path_edges.iter() {
+        facts.entry(*func).or_insert_with(|| {
+            let graph = program.graph(*func);
+            let empty = NodeInfo {
+                before: PowerSet::empty(),
+                after: PowerSet::empty(),
+            };
+
+            graph
+                .get_all_node_ids()
+                .iter()
+                .map(|id| (*id, empty.clone()))
+                .collect()
+        });
+    }
+
+    for (func, _, point, d) in solver.path_edges {
+        if d == zero {
+            continue;
+        }
+
+        let info = facts.get_mut(&func).unwrap();
+        match point {
+            Point::In(id) => info.get_mut(&id).unwrap().before.insert(d),
+            Point::Out(id) => info.get_mut(&id).unwrap().after.insert(d),
+        }
+    }
+
+    let mut summaries = solver.summaries;
+    for exits in summaries.values_mut() {
+        exits.remove(&zero);
+    }
+
+    Solution { facts, summaries }
+}
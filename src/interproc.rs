@@ -0,0 +1,389 @@
+//! Analysis of whole programs, made of many functions which call each other.
+//!
+//! A `Program` gives a `Graph` for every function, and tells which nodes are
+//! call sites, which functions each of them calls, and where each call
+//! returns to. By default a call site's successors are its return sites, so
+//! the facts which hold after a call are the `after` facts of the call site.
+//!
+//! `solve` analyzes functions bottom-up over the `CallGraph`, so the functions
+//! a function calls have always been summarized before it is solved. Each
+//! function is solved by an ordinary `Analyzer`, whose `trans` can look up the
+//! summaries of the functions called by a node and apply them.
+//!
+//! ```ignore
+//! let res = interproc::solve(&program, empty_summary, |func, graph, summaries| {
+//!     let trans = |node: &MyNode, fact| match program.callees(func, node) {
+//!         [] => trans(node, fact),
+//!         callees => apply(callees.iter().map(|c| summaries.get(*c)), fact),
+//!     };
+//!
+//!     Analyzer::new_forward(top.clone(), trans, join).solve(graph)
+//! }, summarize);
+//! ```
+
+use std::hash::Hash;
+
+use super::{Fact, Graph, HashMap, HashSet, Node, NodeInfo};
+
+/// A program made of functions, each of which has its own graph
+pub trait Program<N: Node> {
+    /// The unique id for a function
+    type FuncId: Copy + Eq + Hash;
+
+    /// The graph type of every function
+    type Graph: Graph<N>;
+
+    /// Get the ids of every function in the program
+    fn functions(&self) -> &[Self::FuncId];
+
+    /// Get the graph of a function
+    fn graph(&self, func: Self::FuncId) -> &Self::Graph;
+
+    /// Get the functions which may be called by `node` in `func`. This is
+    /// empty if `node` isn't a call site. Callees which aren't in `functions`
+    /// are external, like library functions, and have no graph to solve.
+    fn callees(&self, func: Self::FuncId, node: &N) -> &[Self::FuncId];
+
+    /// Get the call sites of `func`. By default these are the nodes with any
+    /// callees.
+    fn call_sites(&self, func: Self::FuncId) -> Vec<N::NodeId> {
+        let graph = self.graph(func);
+
+        graph
+            .get_all_node_ids()
+            .iter()
+            .copied()
+            .filter(|id| !self.callees(func, graph.get(*id)).is_empty())
+            .collect()
+    }
+
+    /// Get the return sites of the call site `id` in `func`, where execution
+    /// carries on once the call returns. By default these are the successors
+    /// of the call site. The solvers which follow a function's own graph, like
+    /// `solve` and `callstrings`, assume this default.
+    fn return_sites(&self, func: Self::FuncId, id: N::NodeId) -> Vec<N::NodeId> {
+        self.graph(func).get_succs(id).to_vec()
+    }
+}
+
+/// Get the nodes which come after `id` in `func`: its return sites if it's a
+/// call site, or its successors otherwise
+pub(crate) fn nexts<N, P>(program: &P, func: P::FuncId, id: N::NodeId) -> Vec<N::NodeId>
+where
+    N: Node,
+    P: Program<N>,
+{
+    let graph = program.graph(func);
+
+    if program.callees(func, graph.get(id)).is_empty() {
+        graph.get_succs(id).to_vec()
+    } else {
+        program.return_sites(func, id)
+    }
+}
+
+/// The functions which call each other in a `Program`
+#[derive(Clone, Debug)]
+pub struct CallGraph<Id: Eq + Hash> {
+    funcs: Vec<Id>,
+    callees: HashMap<Id, Vec<Id>>,
+    callers: HashMap<Id, Vec<Id>>,
+
+    /// The strongly connected components, callees first
+    sccs: Vec<Vec<Id>>,
+
+    /// The functions which are part of a cycle of calls
+    recursive: HashSet<Id>,
+}
+
+impl<Id: Copy + Eq + Hash> CallGraph<Id> {
+    /// Find the calls made by every function in `program`. Calls to external
+    /// functions, which aren't in `functions`, are left out.
+    pub fn new<N, P>(program: &P) -> Self
+    where
+        N: Node,
+        P: Program<N, FuncId = Id>,
+    {
+        let funcs = program.functions().to_vec();
+        let mut callees: HashMap<Id, Vec<Id>> = funcs.iter().map(|f| (*f, Vec::new())).collect();
+        let mut callers: HashMap<Id, Vec<Id>> = funcs.iter().map(|f| (*f, Vec::new())).collect();
+
+        for func in funcs.iter() {
+            let graph = program.graph(*func);
+
+            for id in program.call_sites(*func) {
+                for callee in program.callees(*func, graph.get(id)) {
+                    let calls = callees.get_mut(func).unwrap();
+                    if !callers.contains_key(callee) || calls.contains(callee) {
+                        continue;
+                    }
+
+                    calls.push(*callee);
+                    callers.get_mut(callee).unwrap().push(*func);
+                }
+            }
+        }
+
+        let mut calls = Self {
+            funcs,
+            callees,
+            callers,
+            sccs: Vec::new(),
+            recursive: HashSet::default(),
+        };
+
+        calls.sccs = Tarjan::new(&calls).run();
+        for scc in calls.sccs.iter() {
+            if scc.len() > 1 || calls.callees(scc[0]).contains(&scc[0]) {
+                calls.recursive.extend(scc.iter().copied());
+            }
+        }
+
+        calls
+    }
+
+    /// Get the functions called by `func`
+    pub fn callees(&self, func: Id) -> &[Id] {
+        self.callees.get(&func).map_or(&[], |c| c.as_slice())
+    }
+
+    /// Get the functions which call `func`
+    pub fn callers(&self, func: Id) -> &[Id] {
+        self.callers.get(&func).map_or(&[], |c| c.as_slice())
+    }
+
+    /// Check whether `func` is part of a cycle of calls, including calling
+    /// itself
+    pub fn is_recursive(&self, func: Id) -> bool {
+        self.recursive.contains(&func)
+    }
+
+    /// Get the strongly connected components of the call graph, which are the
+    /// groups of functions that can all reach each other through calls. Every
+    /// component comes after all of the components it calls, so solving them
+    /// in order works bottom-up.
+    pub fn sccs(&self) -> &[Vec<Id>] {
+        &self.sccs
+    }
+}
+
+/// State for Tarjan's strongly connected components algorithm
+struct Tarjan<'a, Id: Eq + Hash> {
+    graph: &'a CallGraph<Id>,
+    index: HashMap<Id, usize>,
+    lowlink: HashMap<Id, usize>,
+    stack: Vec<Id>,
+    on_stack: HashSet<Id>,
+    sccs: Vec<Vec<Id>>,
+}
+
+impl<'a, Id: Copy + Eq + Hash> Tarjan<'a, Id> {
+    fn new(graph: &'a CallGraph<Id>) -> Self {
+        Self {
+            graph,
+            index: HashMap::default(),
+            lowlink: HashMap::default(),
+            stack: Vec::new(),
+            on_stack: HashSet::default(),
+            sccs: Vec::new(),
+        }
+    }
+
+    fn run(mut self) -> Vec<Vec<Id>> {
+        for func in self.graph.funcs.iter() {
+            if !self.index.contains_key(func) {
+                self.visit(*func);
+            }
+        }
+
+        self.sccs
+    }
+
+    /// Visit `root` and everything it calls, iteratively so that deep call
+    /// chains can't overflow the stack
+    fn visit(&mut self, root: Id) {
+        let mut calls = vec![(root, 0)];
+        self.push(root);
+
+        while let Some((func, i)) = calls.last().copied() {
+            match self.graph.callees(func).get(i) {
+                Some(callee) => {
+                    calls.last_mut().unwrap().1 += 1;
+
+                    if !self.index.contains_key(callee) {
+                        self.push(*callee);
+                        calls.push((*callee, 0));
+                    } else if self.on_stack.contains(callee) {
+                        let low = self.lowlink[&func].min(self.index[callee]);
+                        self.lowlink.insert(func, low);
+                    }
+                }
+                None => {
+                    calls.pop();
+
+                    if let Some((caller, _)) = calls.last() {
+                        let low = self.lowlink[caller].min(self.lowlink[&func]);
+                        self.lowlink.insert(*caller, low);
+                    }
+
+                    if self.lowlink[&func] == self.index[&func] {
+                        let mut scc = Vec::new();
+
+                        while let Some(member) = self.stack.pop() {
+                            self.on_stack.remove(&member);
+                            scc.push(member);
+
+                            if member == func {
+                                break;
+                            }
+                        }
+
+                        scc.reverse();
+                        self.sccs.push(scc);
+                    }
+                }
+            }
+        }
+    }
+
+    fn push(&mut self, func: Id) {
+        let index = self.index.len();
+        self.index.insert(func, index);
+        self.lowlink.insert(func, index);
+        self.stack.push(func);
+        self.on_stack.insert(func);
+    }
+}
+
+/// The summaries of the functions solved so far
+#[derive(Clone, Debug)]
+pub struct Summaries<Id: Eq + Hash, S> {
+    summaries: HashMap<Id, S>,
+
+    /// The summary used for functions which haven't been summarized yet
+    init: S,
+}
+
+impl<Id: Copy + Eq + Hash, S> Summaries<Id, S> {
+    /// Get the summary for `func`. Functions in the same cycle of calls as the
+    /// function being solved may not have been summarized yet, and external
+    /// functions never are, in which case this gives the initial summary.
+    pub fn get(&self, func: Id) -> &S {
+        self.summaries.get(&func).unwrap_or(&self.init)
+    }
+}
+
+/// The facts and summary for every function in a program
+pub struct Solution<Id: Eq + Hash, NodeId: Eq + Hash, F: Fact, S> {
+    pub facts: HashMap<Id, HashMap<NodeId, NodeInfo<F>>>,
+    pub summaries: HashMap<Id, S>,
+}
+
+/// Solve every function in `program` bottom-up. `solve` gets each function
+/// along with the summaries of the functions it calls, and `summarize` turns
+/// its facts into a summary.
+///
+/// Functions which call each other in a cycle start out with the `init`
+/// summary, and are solved over and over until their summaries stop changing.
+/// `init` should be the summary of a function which never returns, so that
+/// the summaries only grow. If summaries can grow forever, like intervals,
+/// use `solve_with_widening` instead.
+pub fn solve<N, P, F, S, Solve, Summarize>(
+    program: &P,
+    init: S,
+    solve: Solve,
+    summarize: Summarize,
+) -> Solution<P::FuncId, N::NodeId, F, S>
+where
+    N: Node,
+    P: Program<N>,
+    F: Fact,
+    S: Clone + PartialEq,
+    Solve: FnMut(P::FuncId, &P::Graph, &Summaries<P::FuncId, S>) -> HashMap<N::NodeId, NodeInfo<F>>,
+    Summarize: FnMut(P::FuncId, &HashMap<N::NodeId, NodeInfo<F>>) -> S,
+{
+    run(program, init, None::<fn(&S, &S) -> S>, solve, summarize)
+}
+
+/// Solve every function in `program` bottom-up like `solve`, but widen the
+/// summaries of functions which call each other in a cycle with `widen`, which
+/// is given the previous and the new summary. This makes the solve terminate
+/// for summaries with infinite ascending chains, as long as repeatedly
+/// widening an increasing chain of summaries eventually stabilizes.
+pub fn solve_with_widening<N, P, F, S, Widen, Solve, Summarize>(
+    program: &P,
+    init: S,
+    widen: Widen,
+    solve: Solve,
+    summarize: Summarize,
+) -> Solution<P::FuncId, N::NodeId, F, S>
+where
+    N: Node,
+    P: Program<N>,
+    F: Fact,
+    S: Clone + PartialEq,
+    Widen: FnMut(&S, &S) -> S,
+    Solve: FnMut(P::FuncId, &P::Graph, &Summaries<P::FuncId, S>) -> HashMap<N::NodeId, NodeInfo<F>>,
+    Summarize: FnMut(P::FuncId, &HashMap<N::NodeId, NodeInfo<F>>) -> S,
+{
+    run(program, init, Some(widen), solve, summarize)
+}
+
+fn run<N, P, F, S, Widen, Solve, Summarize>(
+    program: &P,
+    init: S,
+    mut widen: Option<Widen>,
+    mut solve: Solve,
+    mut summarize: Summarize,
+) -> Solution<P::FuncId, N::NodeId, F, S>
+where
+    N: Node,
+    P: Program<N>,
+    F: Fact,
+    S: Clone + PartialEq,
+    Widen: FnMut(&S, &S) -> S,
+    Solve: FnMut(P::FuncId, &P::Graph, &Summaries<P::FuncId, S>) -> HashMap<N::NodeId, NodeInfo<F>>,
+    Summarize: FnMut(P::FuncId, &HashMap<N::NodeId, NodeInfo<F>>) -> S,
+{
+    let calls = CallGraph::new(program);
+    let mut summaries = Summaries {
+        summaries: HashMap::default(),
+        init,
+    };
+    let mut facts = HashMap::default();
+
+    for scc in calls.sccs() {
+        let recursive = calls.is_recursive(scc[0]);
+
+        loop {
+            let mut changed = false;
+
+            for func in scc.iter() {
+                let res = solve(*func, program.graph(*func), &summaries);
+                let mut summary = summarize(*func, &res);
+
+                if let (true, Some(widen), Some(prev)) =
+                    (recursive, widen.as_mut(), summaries.summaries.get(func))
+                {
+                    summary = widen(prev, &summary);
+                }
+
+                if summaries.summaries.get(func) != Some(&summary) {
+                    summaries.summaries.insert(*func, summary);
+                    changed = true;
+                }
+
+                facts.insert(*func, res);
+            }
+
+            if !recursive || !changed {
+                break;
+            }
+        }
+    }
+
+    Solution {
+        facts,
+        summaries: summaries.summaries,
+    }
+}
@@ -0,0 +1,289 @@
+//! Lattices built out of other lattices.
+
+use super::{Bounded, Lattice, Widen};
+use crate::Fact;
+
+/// A pair of lattices, ordered and joined component by component. This runs
+/// two analyses side by side, like constants and intervals.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Product<A, B>(pub A, pub B);
+
+impl<A: Lattice, B: Lattice> Fact for Product<A, B> {}
+
+impl<A: Lattice, B: Lattice> Lattice for Product<A, B> {
+    fn bottom() -> Self {
+        Product(A::bottom(), B::bottom())
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        Product(self.0.join(&other.0), self.1.join(&other.1))
+    }
+}
+
+impl<A: Bounded, B: Bounded> Bounded for Product<A, B> {
+    fn top() -> Self {
+        Product(A::top(), B::top())
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        Product(self.0.meet(&other.0), self.1.meet(&other.1))
+    }
+}
+
+impl<A: Widen, B: Widen> Widen for Product<A, B> {
+    fn widen(&self, next: &Self) -> Self {
+        Product(self.0.widen(&next.0), self.1.widen(&next.1))
+    }
+}
+
+/// A lattice with a new bottom added below it. This is often used to tell
+/// unreachable code, which is `Bottom`, apart from code which is reachable but
+/// has nothing known about it, which is `Lift(F::bottom())`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Lifted<F> {
+    Bottom,
+    Lift(F),
+}
+
+impl<F> Lifted<F> {
+    /// Get the lifted fact, unless this is the new bottom
+    pub fn as_lifted(&self) -> Option<&F> {
+        match self {
+            Lifted::Bottom => None,
+            Lifted::Lift(fact) => Some(fact),
+        }
+    }
+}
+
+impl<F: Lattice> Fact for Lifted<F> {}
+
+impl<F: Lattice> Lattice for Lifted<F> {
+    fn bottom() -> Self {
+        Lifted::Bottom
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Lifted::Bottom, f) | (f, Lifted::Bottom) => f.clone(),
+            (Lifted::Lift(a), Lifted::Lift(b)) => Lifted::Lift(a.join(b)),
+        }
+    }
+}
+
+impl<F: Bounded> Bounded for Lifted<F> {
+    fn top() -> Self {
+        Lifted::Lift(F::top())
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Lifted::Lift(a), Lifted::Lift(b)) => Lifted::Lift(a.meet(b)),
+            _ => Lifted::Bottom,
+        }
+    }
+}
+
+impl<F: Widen> Widen for Lifted<F> {
+    fn widen(&self, next: &Self) -> Self {
+        match (self, next) {
+            (Lifted::Lift(a), Lifted::Lift(b)) => Lifted::Lift(a.widen(b)),
+            _ => self.join(next),
+        }
+    }
+}
+
+/// A lattice turned upside down, so `join` is the `meet` of the original
+/// lattice and `bottom` is its `top`. This turns a may problem into a must
+/// problem, and the other way around.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Dual<F>(pub F);
+
+impl<F: Bounded> Fact for Dual<F> {}
+
+impl<F: Bounded> Lattice for Dual<F> {
+    fn bottom() -> Self {
+        Dual(F::top())
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        Dual(self.0.meet(&other.0))
+    }
+}
+
+impl<F: Bounded> Bounded for Dual<F> {
+    fn top() -> Self {
+        Dual(F::bottom())
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        Dual(self.0.join(&other.0))
+    }
+}
+
+/// A product of two lattices which are kept consistent with each other by a
+/// `reduce` function, which tightens each fact using what the other one
+/// knows. For example, knowing that a variable is positive rules out the
+/// non-positive part of its interval.
+///
+/// `reduce` is given to `new`, and every fact made out of others by `join`,
+/// `meet`, `widen` and `map` keeps it, so it can be a closure with its own
+/// state. `new`, `join`, `meet` and `map` reduce the facts they make, so as
+/// long as `trans` changes facts with `map`, the facts coming out of `trans`
+/// and `join` are as tight as `reduce` can make them. `bottom` and `top` have
+/// no facts to take `reduce` from, and are assumed to be reduced already, so
+/// a solve should start from a fact made with `new`, for example one given to
+/// `with_entry_fact`.
+///
+/// Widening doesn't reduce, since that could keep a fact from stabilizing.
+pub struct Reduced<A, B, R = fn(&mut A, &mut B)> {
+    a: A,
+    b: B,
+    reduce: Option<R>,
+}
+
+impl<A, B, R: Fn(&mut A, &mut B)> Reduced<A, B, R> {
+    /// Pair up two facts and reduce them with `reduce`
+    pub fn new(mut a: A, mut b: B, reduce: R) -> Self {
+        reduce(&mut a, &mut b);
+
+        Self {
+            a,
+            b,
+            reduce: Some(reduce),
+        }
+    }
+
+    /// Get the first fact
+    pub fn first(&self) -> &A {
+        &self.a
+    }
+
+    /// Get the second fact
+    pub fn second(&self) -> &B {
+        &self.b
+    }
+
+    /// Split into both facts
+    pub fn into_parts(self) -> (A, B) {
+        (self.a, self.b)
+    }
+
+    /// Change both facts with `f`, and reduce the result with the `reduce`
+    /// of `self`, if it has one
+    pub fn map<Map: FnOnce(A, B) -> (A, B)>(self, f: Map) -> Self {
+        let (mut a, mut b) = f(self.a, self.b);
+        if let Some(reduce) = self.reduce.as_ref() {
+            reduce(&mut a, &mut b);
+        }
+
+        Self {
+            a,
+            b,
+            reduce: self.reduce,
+        }
+    }
+}
+
+impl<A, B, R: Clone + Fn(&mut A, &mut B)> Reduced<A, B, R> {
+    /// Pair up two facts, reducing them with the `reduce` of `self` or
+    /// `other`, if either has one
+    fn combine(&self, other: &Self, mut a: A, mut b: B) -> Self {
+        let reduce = self.reduce.as_ref().or(other.reduce.as_ref());
+        if let Some(reduce) = reduce {
+            reduce(&mut a, &mut b);
+        }
+
+        Self {
+            a,
+            b,
+            reduce: reduce.cloned(),
+        }
+    }
+}
+
+impl<A: Clone, B: Clone, R: Clone> Clone for Reduced<A, B, R> {
+    fn clone(&self) -> Self {
+        Self {
+            a: self.a.clone(),
+            b: self.b.clone(),
+            reduce: self.reduce.clone(),
+        }
+    }
+}
+
+/// Facts are equal when both halves are, whatever their `reduce` is
+impl<A: PartialEq, B: PartialEq, R> PartialEq for Reduced<A, B, R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.a == other.a && self.b == other.b
+    }
+}
+
+impl<A: std::fmt::Debug, B: std::fmt::Debug, R> std::fmt::Debug for Reduced<A, B, R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_tuple("Reduced")
+            .field(&self.a)
+            .field(&self.b)
+            .finish()
+    }
+}
+
+impl<A, B, R> Fact for Reduced<A, B, R>
+where
+    A: Lattice,
+    B: Lattice,
+    R: Clone + Fn(&mut A, &mut B),
+{
+}
+
+impl<A, B, R> Lattice for Reduced<A, B, R>
+where
+    A: Lattice,
+    B: Lattice,
+    R: Clone + Fn(&mut A, &mut B),
+{
+    fn bottom() -> Self {
+        Self {
+            a: A::bottom(),
+            b: B::bottom(),
+            reduce: None,
+        }
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        self.combine(other, self.a.join(&other.a), self.b.join(&other.b))
+    }
+}
+
+impl<A, B, R> Bounded for Reduced<A, B, R>
+where
+    A: Bounded,
+    B: Bounded,
+    R: Clone + Fn(&mut A, &mut B),
+{
+    fn top() -> Self {
+        Self {
+            a: A::top(),
+            b: B::top(),
+            reduce: None,
+        }
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        self.combine(other, self.a.meet(&other.a), self.b.meet(&other.b))
+    }
+}
+
+impl<A, B, R> Widen for Reduced<A, B, R>
+where
+    A: Widen,
+    B: Widen,
+    R: Clone + Fn(&mut A, &mut B),
+{
+    fn widen(&self, next: &Self) -> Self {
+        Self {
+            a: self.a.widen(&next.a),
+            b: self.b.widen(&next.b),
+            reduce: self.reduce.clone().or_else(|| next.reduce.clone()),
+        }
+    }
+}
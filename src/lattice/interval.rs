@@ -0,0 +1,324 @@
+//! Integer intervals, with bounds which may be infinite.
+
+use std::cmp::Ordering;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use super::{Bounded, Lattice, Widen};
+use crate::Fact;
+
+/// One end of an interval
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Bound {
+    NegInf,
+    Int(i64),
+    PosInf,
+}
+
+impl Bound {
+    /// The sign of the bound, as -1, 0 or 1
+    fn signum(self) -> i64 {
+        match self {
+            Bound::NegInf => -1,
+            Bound::Int(n) => n.signum(),
+            Bound::PosInf => 1,
+        }
+    }
+
+    /// The infinity with the given sign
+    fn inf(sign: i64) -> Self {
+        if sign < 0 {
+            Bound::NegInf
+        } else {
+            Bound::PosInf
+        }
+    }
+
+    /// Add two bounds, going to infinity on overflow. The sum of opposite
+    /// infinities could be anything, so it gives `unknown`, which should be
+    /// `NegInf` for a lower bound and `PosInf` for an upper bound.
+    fn add(self, other: Self, unknown: Self) -> Self {
+        match (self, other) {
+            (Bound::Int(a), Bound::Int(b)) => a
+                .checked_add(b)
+                .map_or_else(|| Bound::inf(a.signum()), Bound::Int),
+            (Bound::NegInf, Bound::PosInf) | (Bound::PosInf, Bound::NegInf) => unknown,
+            (Bound::Int(_), inf) | (inf, _) => inf,
+        }
+    }
+
+    /// Multiply two bounds, going to infinity on overflow. Zero times infinity
+    /// is zero.
+    fn mul(self, other: Self) -> Self {
+        match (self, other) {
+            (Bound::Int(a), Bound::Int(b)) => a
+                .checked_mul(b)
+                .map_or_else(|| Bound::inf(a.signum() * b.signum()), Bound::Int),
+            (Bound::Int(0), _) | (_, Bound::Int(0)) => Bound::Int(0),
+            (a, b) => Bound::inf(a.signum() * b.signum()),
+        }
+    }
+}
+
+/// Negating `i64::MIN` overflows to positive infinity, like the other
+/// arithmetic on bounds
+impl Neg for Bound {
+    type Output = Bound;
+
+    fn neg(self) -> Bound {
+        match self {
+            Bound::NegInf => Bound::PosInf,
+            Bound::Int(n) => n.checked_neg().map_or(Bound::PosInf, Bound::Int),
+            Bound::PosInf => Bound::NegInf,
+        }
+    }
+}
+
+impl From<i64> for Bound {
+    fn from(n: i64) -> Self {
+        Bound::Int(n)
+    }
+}
+
+/// The bounds of a non-empty `Interval`, with `lo <= hi`. These can only be
+/// made by `Interval`, which makes sure they are in order.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Bounds {
+    lo: Bound,
+    hi: Bound,
+}
+
+impl Bounds {
+    /// The smallest integer in the interval
+    pub fn lo(&self) -> Bound {
+        self.lo
+    }
+
+    /// The largest integer in the interval
+    pub fn hi(&self) -> Bound {
+        self.hi
+    }
+}
+
+/// A set of integers `lo..=hi`, which is either empty or has `lo <= hi`. The
+/// empty interval is the bottom of the lattice, and intervals are joined by
+/// taking the smallest interval containing both.
+///
+/// Intervals can grow forever, so solving with them needs widening, which
+/// `Analyzer::with_widening` provides.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Interval {
+    Empty,
+    Range(Bounds),
+}
+
+impl Interval {
+    /// The interval `lo..=hi`, which is empty if `lo > hi`
+    pub fn new<Lo: Into<Bound>, Hi: Into<Bound>>(lo: Lo, hi: Hi) -> Self {
+        let (lo, hi) = (lo.into(), hi.into());
+
+        if lo > hi || lo == Bound::PosInf || hi == Bound::NegInf {
+            Interval::Empty
+        } else {
+            Interval::Range(Bounds { lo, hi })
+        }
+    }
+
+    /// The interval containing only `n`
+    pub fn constant(n: i64) -> Self {
+        Interval::new(n, n)
+    }
+
+    /// The interval containing every integer
+    pub fn full() -> Self {
+        Interval::new(Bound::NegInf, Bound::PosInf)
+    }
+
+    /// The result of arithmetic on non-empty intervals, which is never empty.
+    /// Both bounds may have overflowed to the same infinity, in which case the
+    /// finite one is kept at the edge of `i64` instead.
+    fn around(lo: Bound, hi: Bound) -> Self {
+        let lo = lo.min(Bound::Int(i64::MAX));
+        let hi = hi.max(Bound::Int(i64::MIN));
+        Interval::new(lo, hi)
+    }
+
+    /// Check whether the interval contains `n`
+    pub fn contains(&self, n: i64) -> bool {
+        match self {
+            Interval::Empty => false,
+            Interval::Range(r) => r.lo <= Bound::Int(n) && Bound::Int(n) <= r.hi,
+        }
+    }
+
+    /// Check whether every integer in `self` is in `other`
+    pub fn is_subset(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Interval::Empty, _) => true,
+            (_, Interval::Empty) => false,
+            (Interval::Range(a), Interval::Range(b)) => b.lo <= a.lo && a.hi <= b.hi,
+        }
+    }
+
+    /// Keep only the integers which compare to `n` as `ordering`, such as when
+    /// refining a variable after a test like `x < n`
+    pub fn restrict(&self, ordering: Ordering, n: i64) -> Self {
+        let bound = match ordering {
+            Ordering::Less => Interval::new(
+                Bound::NegInf,
+                Bound::Int(n).add(Bound::Int(-1), Bound::PosInf),
+            ),
+            Ordering::Equal => Interval::constant(n),
+            Ordering::Greater => Interval::new(
+                Bound::Int(n).add(Bound::Int(1), Bound::NegInf),
+                Bound::PosInf,
+            ),
+        };
+
+        self.meet(&bound)
+    }
+
+    /// Widen `self` towards `next` like `widen`, but stop at the nearest of
+    /// `thresholds` before going to infinity. `thresholds` must be sorted.
+    pub fn widen_with(&self, next: &Self, thresholds: &[i64]) -> Self {
+        match (self, next) {
+            (Interval::Empty, _) => *next,
+            (_, Interval::Empty) => *self,
+            (Interval::Range(r), Interval::Range(s)) => {
+                let (a, b, c, d) = (&r.lo, &r.hi, &s.lo, &s.hi);
+                let lo = if c < a {
+                    thresholds
+                        .iter()
+                        .rev()
+                        .map(|t| Bound::Int(*t))
+                        .find(|t| t <= c)
+                        .unwrap_or(Bound::NegInf)
+                } else {
+                    *a
+                };
+
+                let hi = if d > b {
+                    thresholds
+                        .iter()
+                        .map(|t| Bound::Int(*t))
+                        .find(|t| t >= d)
+                        .unwrap_or(Bound::PosInf)
+                } else {
+                    *b
+                };
+
+                Interval::new(lo, hi)
+            }
+        }
+    }
+}
+
+impl Fact for Interval {}
+
+impl Lattice for Interval {
+    fn bottom() -> Self {
+        Interval::Empty
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Interval::Empty, i) | (i, Interval::Empty) => *i,
+            (Interval::Range(r), Interval::Range(s)) => {
+                Interval::new(r.lo.min(s.lo), r.hi.max(s.hi))
+            }
+        }
+    }
+}
+
+impl Bounded for Interval {
+    fn top() -> Self {
+        Interval::full()
+    }
+
+    /// The integers in both intervals
+    fn meet(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Interval::Range(r), Interval::Range(s)) => {
+                Interval::new(r.lo.max(s.lo), r.hi.min(s.hi))
+            }
+            _ => Interval::Empty,
+        }
+    }
+}
+
+/// ```plain
+/// widen([a, b], [c, d]) = [c < a ? -inf : a, d > b ? +inf : b]
+/// ```
+impl Widen for Interval {
+    fn widen(&self, next: &Self) -> Self {
+        self.widen_with(next, &[])
+    }
+}
+
+impl Add for Interval {
+    type Output = Interval;
+
+    fn add(self, other: Interval) -> Interval {
+        match (self, other) {
+            (Interval::Range(r), Interval::Range(s)) => {
+                Interval::around(r.lo.add(s.lo, Bound::NegInf), r.hi.add(s.hi, Bound::PosInf))
+            }
+            _ => Interval::Empty,
+        }
+    }
+}
+
+impl Neg for Interval {
+    type Output = Interval;
+
+    fn neg(self) -> Interval {
+        match self {
+            Interval::Empty => Interval::Empty,
+            Interval::Range(r) => Interval::around(-r.hi, -r.lo),
+        }
+    }
+}
+
+impl Sub for Interval {
+    type Output = Interval;
+
+    fn sub(self, other: Interval) -> Interval {
+        self + -other
+    }
+}
+
+impl Mul for Interval {
+    type Output = Interval;
+
+    fn mul(self, other: Interval) -> Interval {
+        match (self, other) {
+            (Interval::Range(r), Interval::Range(s)) => {
+                let products = [
+                    r.lo.mul(s.lo),
+                    r.lo.mul(s.hi),
+                    r.hi.mul(s.lo),
+                    r.hi.mul(s.hi),
+                ];
+                let lo = products.iter().min().unwrap();
+                let hi = products.iter().max().unwrap();
+
+                Interval::around(*lo, *hi)
+            }
+            _ => Interval::Empty,
+        }
+    }
+}
+
+impl std::fmt::Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let bound = |b: &Bound| match b {
+            Bound::NegInf => "-inf".to_string(),
+            Bound::Int(n) => n.to_string(),
+            Bound::PosInf => "+inf".to_string(),
+        };
+
+        match self {
+            Interval::Empty => write!(f, "empty"),
+            Interval::Range(r) => write!(f, "[{}, {}]", bound(&r.lo), bound(&r.hi)),
+        }
+    }
+}
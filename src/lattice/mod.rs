@@ -0,0 +1,229 @@
+//! Building blocks for facts which form a lattice.
+//!
+//! A `Lattice` knows how to `join` itself with another fact, so analyses over
+//! lattices only need to provide `trans`, and can use `join_all` as the join
+//! function. The lattices here are ordered the usual way, with information
+//! growing upwards from `bottom`. Since `bottom` is the identity of `join`, it
+//! is what `Analyzer` calls the top fact.
+//!
+//! ```ignore
+//! let top = Env::bottom();
+//! let mut analyzer = Analyzer::new_forward(top, trans, lattice::join_all);
+//!
+//! // Or, equivalently
+//! let mut analyzer = Analyzer::new_forward_lattice(trans);
+//! ```
+//!
+//! Bigger lattices can be built out of smaller ones with `Product`, `PowerSet`,
+//! `MapLattice`, `Lifted` and `Dual`. `Reduced` is like `Product`, but lets
+//! the two halves tighten each other, such as a `Sign` and an `Interval` for
+//! the same variable.
+
+use std::hash::Hash;
+
+use super::{Fact, HashMap};
+
+mod combine;
+mod interval;
+mod set;
+mod sign;
+
+pub use self::combine::{Dual, Lifted, Product, Reduced};
+pub use self::interval::{Bound, Bounds, Interval};
+pub use self::set::{May, Must, PowerSet, SetKind};
+pub use self::sign::Sign;
+
+/// A fact with a least element and a least upper bound
+pub trait Lattice: Fact {
+    /// The least element, which is the identity of `join`
+    fn bottom() -> Self;
+
+    /// The least upper bound of two facts
+    fn join(&self, other: &Self) -> Self;
+}
+
+/// A lattice which also has a greatest element and a greatest lower bound
+pub trait Bounded: Lattice {
+    /// The greatest element, which is the identity of `meet`
+    fn top() -> Self;
+
+    /// The greatest lower bound of two facts
+    fn meet(&self, other: &Self) -> Self;
+}
+
+/// A lattice with a widening operator. Lattices with infinite ascending
+/// chains, like `Interval`, can use it with `Analyzer::with_widening` to make
+/// sure the solve terminates.
+pub trait Widen: Lattice {
+    /// Get a fact at least as large as both `self` and `next`. Repeatedly
+    /// widening with the elements of any increasing chain must eventually
+    /// stop changing.
+    fn widen(&self, next: &Self) -> Self;
+}
+
+/// Join any number of facts, giving `bottom` for none. This can be passed to
+/// `Analyzer` as the join function for any lattice.
+pub fn join_all<L: Lattice>(facts: Vec<L>) -> L {
+    let mut facts = facts.into_iter();
+
+    match facts.next() {
+        Some(first) => facts.fold(first, |acc, f| acc.join(&f)),
+        None => L::bottom(),
+    }
+}
+
+/// The flat lattice over `T`, where every value is only related to `Bottom`
+/// and `Top`. In constant propagation, `Bottom` means no value has been seen
+/// yet, and `Top` means more than one value has been seen.
+///
+/// ```plain
+///          Top
+///       /  |  \
+/// Const(a) .. Const(z)
+///       \  |  /
+///         Bottom
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Flat<T> {
+    Bottom,
+    Const(T),
+    Top,
+}
+
+impl<T> Flat<T> {
+    /// Get the value, if there is exactly one
+    pub fn as_const(&self) -> Option<&T> {
+        match self {
+            Flat::Const(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Combine two values with `op`. The result is `Bottom` if either value is
+    /// `Bottom`, and otherwise `Top` if either value is `Top`.
+    pub fn map2<U, R, Op>(&self, other: &Flat<U>, op: Op) -> Flat<R>
+    where
+        Op: FnOnce(&T, &U) -> R,
+    {
+        match (self, other) {
+            (Flat::Bottom, _) | (_, Flat::Bottom) => Flat::Bottom,
+            (Flat::Top, _) | (_, Flat::Top) => Flat::Top,
+            (Flat::Const(a), Flat::Const(b)) => Flat::Const(op(a, b)),
+        }
+    }
+}
+
+impl<T: Clone + PartialEq> Fact for Flat<T> {}
+
+impl<T: Clone + PartialEq> Lattice for Flat<T> {
+    fn bottom() -> Self {
+        Flat::Bottom
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Flat::Bottom, f) | (f, Flat::Bottom) => f.clone(),
+            (Flat::Const(a), Flat::Const(b)) if a == b => Flat::Const(a.clone()),
+            _ => Flat::Top,
+        }
+    }
+}
+
+impl<T: Clone + PartialEq> Bounded for Flat<T> {
+    fn top() -> Self {
+        Flat::Top
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Flat::Top, f) | (f, Flat::Top) => f.clone(),
+            (Flat::Const(a), Flat::Const(b)) if a == b => Flat::Const(a.clone()),
+            _ => Flat::Bottom,
+        }
+    }
+}
+
+/// A map from keys to lattice values, joined pointwise. Keys which aren't in
+/// the map are `bottom`, and keys are never stored with a `bottom` value, so
+/// two maps are equal exactly when they agree on every key.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapLattice<K: Eq + Hash, V> {
+    map: HashMap<K, V>,
+}
+
+impl<K: Clone + Eq + Hash, V: Lattice> MapLattice<K, V> {
+    /// Get the value for `key`
+    pub fn get(&self, key: &K) -> V {
+        self.map.get(key).cloned().unwrap_or_else(V::bottom)
+    }
+
+    /// Set the value for `key`, replacing the previous one
+    pub fn insert(&mut self, key: K, value: V) {
+        if value == V::bottom() {
+            self.map.remove(&key);
+        } else {
+            self.map.insert(key, value);
+        }
+    }
+
+    /// Iterate over the keys which aren't `bottom`
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.map.iter()
+    }
+
+    /// Combine two maps key by key with `op`, which is given `bottom` for
+    /// keys missing from one of the maps. Keys missing from both maps are
+    /// assumed to stay `bottom`.
+    pub fn merge_with<Op>(&self, other: &Self, mut op: Op) -> Self
+    where
+        Op: FnMut(&V, &V) -> V,
+    {
+        let mut res = self.clone();
+        let bottom = V::bottom();
+
+        for (key, value) in self.map.iter() {
+            let merged = op(value, other.map.get(key).unwrap_or(&bottom));
+            res.insert(key.clone(), merged);
+        }
+
+        for (key, value) in other.map.iter() {
+            if !self.map.contains_key(key) {
+                res.insert(key.clone(), op(&bottom, value));
+            }
+        }
+
+        res
+    }
+}
+
+impl<K: Clone + Eq + Hash, V: Lattice> Fact for MapLattice<K, V> {}
+
+impl<K: Clone + Eq + Hash, V: Lattice> Lattice for MapLattice<K, V> {
+    fn bottom() -> Self {
+        Self {
+            map: HashMap::default(),
+        }
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        self.merge_with(other, V::join)
+    }
+}
+
+impl<K: Clone + Eq + Hash, V: Widen> Widen for MapLattice<K, V> {
+    fn widen(&self, next: &Self) -> Self {
+        self.merge_with(next, V::widen)
+    }
+}
+
+impl<K: Clone + Eq + Hash, V: Lattice> std::iter::FromIterator<(K, V)> for MapLattice<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut res = Self::bottom();
+
+        for (key, value) in iter {
+            res.insert(key, value);
+        }
+
+        res
+    }
+}
@@ -0,0 +1,171 @@
+//! Sets of elements, joined by union or by intersection.
+
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use super::{Bounded, Lattice};
+use crate::{Fact, HashSet};
+
+/// Whether a `PowerSet` is joined by union or by intersection
+pub trait SetKind {
+    /// Whether facts must hold on every path, and are joined by intersection
+    const MUST: bool;
+}
+
+/// Sets for may problems, like liveness, where `bottom` is the empty set and
+/// `join` is union
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct May;
+
+impl SetKind for May {
+    const MUST: bool = false;
+}
+
+/// Sets for must problems, like available expressions, where `bottom` is the
+/// set of everything and `join` is intersection
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Must;
+
+impl SetKind for Must {
+    const MUST: bool = true;
+}
+
+/// A set of elements of `T`, which may also be the set of everything without
+/// listing it. Whether it is joined by union or intersection depends on `K`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PowerSet<T: Eq + Hash, K = May> {
+    /// The elements of the set, or `None` for the set of everything
+    set: Option<HashSet<T>>,
+
+    _kind: PhantomData<K>,
+}
+
+impl<T: Clone + Eq + Hash, K: Clone> PowerSet<T, K> {
+    /// The empty set
+    pub fn empty() -> Self {
+        Self {
+            set: Some(HashSet::default()),
+            _kind: PhantomData,
+        }
+    }
+
+    /// The set of everything
+    pub fn all() -> Self {
+        Self {
+            set: None,
+            _kind: PhantomData,
+        }
+    }
+
+    /// Check whether this is the set of everything
+    pub fn is_all(&self) -> bool {
+        self.set.is_none()
+    }
+
+    /// Get the elements of the set, unless it is the set of everything
+    pub fn as_set(&self) -> Option<&HashSet<T>> {
+        self.set.as_ref()
+    }
+
+    /// Check whether the set contains `elem`
+    pub fn contains(&self, elem: &T) -> bool {
+        match &self.set {
+            None => true,
+            Some(set) => set.contains(elem),
+        }
+    }
+
+    /// Add `elem` to the set
+    pub fn insert(&mut self, elem: T) {
+        if let Some(set) = &mut self.set {
+            set.insert(elem);
+        }
+    }
+
+    /// Remove `elem` from the set. The set of everything can't list what it
+    /// holds, so removing from it does nothing.
+    pub fn remove(&mut self, elem: &T) {
+        if let Some(set) = &mut self.set {
+            set.remove(elem);
+        }
+    }
+
+    /// Remove the elements which `keep` returns `false` for. Like `remove`,
+    /// this leaves the set of everything as it is, so callers which can see
+    /// it should check `is_all` first.
+    pub fn retain<Keep: FnMut(&T) -> bool>(&mut self, keep: Keep) {
+        if let Some(set) = &mut self.set {
+            set.retain(keep);
+        }
+    }
+
+    /// The elements in either set
+    pub fn union(&self, other: &Self) -> Self {
+        match (&self.set, &other.set) {
+            (Some(a), Some(b)) => Self {
+                set: Some(a.union(b).cloned().collect()),
+                _kind: PhantomData,
+            },
+            _ => Self::all(),
+        }
+    }
+
+    /// The elements in both sets
+    pub fn intersect(&self, other: &Self) -> Self {
+        match (&self.set, &other.set) {
+            (Some(a), Some(b)) => Self {
+                set: Some(a.intersection(b).cloned().collect()),
+                _kind: PhantomData,
+            },
+            (None, _) => other.clone(),
+            (_, None) => self.clone(),
+        }
+    }
+}
+
+impl<T: Clone + Eq + Hash, K: Clone + PartialEq + SetKind> Fact for PowerSet<T, K> {}
+
+impl<T: Clone + Eq + Hash, K: Clone + PartialEq + SetKind> Lattice for PowerSet<T, K> {
+    fn bottom() -> Self {
+        if K::MUST {
+            Self::all()
+        } else {
+            Self::empty()
+        }
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        if K::MUST {
+            self.intersect(other)
+        } else {
+            self.union(other)
+        }
+    }
+}
+
+impl<T: Clone + Eq + Hash, K: Clone + PartialEq + SetKind> Bounded for PowerSet<T, K> {
+    fn top() -> Self {
+        if K::MUST {
+            Self::empty()
+        } else {
+            Self::all()
+        }
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        if K::MUST {
+            self.union(other)
+        } else {
+            self.intersect(other)
+        }
+    }
+}
+
+impl<T: Clone + Eq + Hash, K> std::iter::FromIterator<T> for PowerSet<T, K> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self {
+            set: Some(iter.into_iter().collect()),
+            _kind: PhantomData,
+        }
+    }
+}
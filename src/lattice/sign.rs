@@ -0,0 +1,125 @@
+//! The signs of integers.
+
+use std::ops::{Add, Mul, Neg, Sub};
+
+use super::{Bounded, Lattice, Widen};
+use crate::Fact;
+
+/// The sign of an integer. `Bottom` means no value has been seen yet, and
+/// `Top` means values with different signs have been seen.
+///
+/// ```plain
+///        Top
+///     /   |   \
+///   Neg  Zero  Pos
+///     \   |   /
+///       Bottom
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Sign {
+    Bottom,
+    Neg,
+    Zero,
+    Pos,
+    Top,
+}
+
+impl Sign {
+    /// The sign of `n`
+    pub fn of(n: i64) -> Self {
+        match n {
+            0 => Sign::Zero,
+            n if n < 0 => Sign::Neg,
+            _ => Sign::Pos,
+        }
+    }
+
+    /// Check whether `n` has this sign
+    pub fn contains(self, n: i64) -> bool {
+        self.join(&Sign::of(n)) == self
+    }
+}
+
+impl Fact for Sign {}
+
+impl Lattice for Sign {
+    fn bottom() -> Self {
+        Sign::Bottom
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Sign::Bottom, s) | (s, Sign::Bottom) => *s,
+            (a, b) if a == b => *a,
+            _ => Sign::Top,
+        }
+    }
+}
+
+impl Bounded for Sign {
+    fn top() -> Self {
+        Sign::Top
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Sign::Top, s) | (s, Sign::Top) => *s,
+            (a, b) if a == b => *a,
+            _ => Sign::Bottom,
+        }
+    }
+}
+
+/// Signs can only change twice, so widening is just joining
+impl Widen for Sign {
+    fn widen(&self, next: &Self) -> Self {
+        self.join(next)
+    }
+}
+
+impl Add for Sign {
+    type Output = Sign;
+
+    fn add(self, other: Sign) -> Sign {
+        match (self, other) {
+            (Sign::Bottom, _) | (_, Sign::Bottom) => Sign::Bottom,
+            (Sign::Zero, s) | (s, Sign::Zero) => s,
+            (a, b) if a == b => a,
+            _ => Sign::Top,
+        }
+    }
+}
+
+impl Neg for Sign {
+    type Output = Sign;
+
+    fn neg(self) -> Sign {
+        match self {
+            Sign::Neg => Sign::Pos,
+            Sign::Pos => Sign::Neg,
+            s => s,
+        }
+    }
+}
+
+impl Sub for Sign {
+    type Output = Sign;
+
+    fn sub(self, other: Sign) -> Sign {
+        self + -other
+    }
+}
+
+impl Mul for Sign {
+    type Output = Sign;
+
+    fn mul(self, other: Sign) -> Sign {
+        match (self, other) {
+            (Sign::Bottom, _) | (_, Sign::Bottom) => Sign::Bottom,
+            (Sign::Zero, _) | (_, Sign::Zero) => Sign::Zero,
+            (Sign::Top, _) | (_, Sign::Top) => Sign::Top,
+            (a, b) if a == b => Sign::Pos,
+            _ => Sign::Neg,
+        }
+    }
+}
@@ -1,10 +1,40 @@
+pub mod analyses;
+pub mod callstrings;
+pub mod cfg;
+pub mod check;
+pub mod dot;
+#[cfg(feature = "gen")]
+pub mod gen;
+pub mod ide;
+pub mod ifds;
+pub mod interproc;
+pub mod lattice;
+pub mod pre;
+pub mod text;
+
 mod analyze;
+mod bidirectional;
 mod hash;
+mod mop;
+mod observe;
+#[cfg(feature = "rayon")]
+mod parallel;
 mod problem;
+mod query;
+mod steps;
+mod together;
 
-pub use analyze::Analyzer;
-pub use hash::HashMap;
+pub use analyze::{Analyzer, Cancelled};
+pub use bidirectional::Bidirectional;
+pub use hash::{HashMap, HashSet};
+pub use mop::Difference;
+#[cfg(feature = "log")]
+pub use observe::LogObserver;
+pub use observe::{Observer, Stats};
 pub use problem::{Backward, Forward};
+pub use query::Query;
+pub use steps::{Step, Steps};
+pub use together::{solve_together, Analyses};
 
 use std::hash::Hash;
 
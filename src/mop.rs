@@ -0,0 +1,126 @@
+use super::problem::Problem;
+use super::{Fact, Graph, Node, NodeInfo};
+use super::{HashMap, HashSet};
+
+/// A node whose facts from the worklist solver, the maximal fixed point, aren't
+/// the facts joined over every path to it
+#[derive(Clone, Debug, PartialEq)]
+pub struct Difference<F: Fact, Id> {
+    /// The node whose facts differ
+    pub id: Id,
+
+    /// The facts found by the worklist solver
+    pub mfp: NodeInfo<F>,
+
+    /// The facts joined over every path
+    pub mop: NodeInfo<F>,
+}
+
+/// Find the meet over all paths solution, by running `trans` along every path
+/// with at most `max_len` nodes, and joining the facts every path gives each
+/// node. Paths start at the starting node with `first`, and with `join(vec![])`
+/// at every other node with nothing to join, and at every node in a cycle
+/// which none of those reach. The worklist solver gives facts to these nodes
+/// too, so they are compared (and pass facts on) the same way. Nodes no path
+/// reaches get `init`.
+///
+/// ```plain
+/// joined(n) = join(trans(p, start(p)) for p in paths(n))
+/// transd(n) = join(trans(n, trans(p, start(p))) for p in paths(n))
+/// ```
+pub(crate) fn solve<F, N, G, Trans, Join, Sort>(
+    graph: &G,
+    first: F,
+    init: &NodeInfo<F>,
+    trans: &mut Trans,
+    join: &mut Join,
+    max_len: usize,
+) -> HashMap<N::NodeId, NodeInfo<F>>
+where
+    F: Fact,
+    N: Node,
+    G: Graph<N>,
+    Trans: FnMut(&N, F) -> F,
+    Join: FnMut(Vec<F>) -> F,
+    Sort: Problem<F, N, G>,
+{
+    // The facts each path gives every node, before and after transforming
+    let mut paths: HashMap<N::NodeId, (Vec<F>, Vec<F>)> = HashMap::default();
+
+    let mut stack = Vec::new();
+    if max_len > 0 {
+        let start = Sort::get_first(graph);
+        let mut sources = vec![start];
+        sources.extend(
+            graph
+                .get_all_node_ids()
+                .iter()
+                .filter(|id| **id != start && Sort::get_joins(graph, **id).is_empty()),
+        );
+
+        // Whatever is left over can only be reached from a cycle of its own
+        let reached = reachable::<F, N, G, Sort>(graph, &sources);
+        sources.extend(
+            graph
+                .get_all_node_ids()
+                .iter()
+                .filter(|id| !reached.contains(id)),
+        );
+
+        stack.push((start, first, 1));
+        for id in sources.into_iter().skip(1) {
+            stack.push((id, join(Vec::new()), 1));
+        }
+    }
+
+    while let Some((id, joined, len)) = stack.pop() {
+        let transd = trans(graph.get(id), joined.clone());
+
+        if len < max_len {
+            for next in Sort::get_nexts(graph, id) {
+                stack.push((*next, transd.clone(), len + 1));
+            }
+        }
+
+        let facts = paths.entry(id).or_default();
+        facts.0.push(joined);
+        facts.1.push(transd);
+    }
+
+    graph
+        .get_all_node_ids()
+        .iter()
+        .map(|id| {
+            let info = match paths.remove(id) {
+                Some((joined, transd)) => {
+                    let mut info = init.clone();
+                    Sort::assign(&mut info, join(joined), join(transd));
+                    info
+                }
+                None => init.clone(),
+            };
+
+            (*id, info)
+        })
+        .collect()
+}
+
+/// Get every node which can be reached from one of `sources`, including them
+fn reachable<F, N, G, Sort>(graph: &G, sources: &[N::NodeId]) -> HashSet<N::NodeId>
+where
+    F: Fact,
+    N: Node,
+    G: Graph<N>,
+    Sort: Problem<F, N, G>,
+{
+    let mut seen = HashSet::default();
+    let mut stack = sources.to_vec();
+
+    while let Some(id) = stack.pop() {
+        if seen.insert(id) {
+            stack.extend(Sort::get_nexts(graph, id));
+        }
+    }
+
+    seen
+}
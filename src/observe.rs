@@ -0,0 +1,117 @@
+use super::HashMap;
+use super::{Fact, Node};
+
+/// Counts gathered over a single solve
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stats<Id>
+where
+    Id: Copy + Eq + std::hash::Hash,
+{
+    /// The total number of times `trans` was called
+    pub trans_calls: usize,
+
+    /// The total number of times `join` was called
+    pub join_calls: usize,
+
+    /// The number of times each node was taken off the worklist
+    pub visits: HashMap<Id, usize>,
+
+    /// The largest the worklist got at any point during the solve
+    pub max_worklist_len: usize,
+}
+
+impl<Id> Default for Stats<Id>
+where
+    Id: Copy + Eq + std::hash::Hash,
+{
+    fn default() -> Self {
+        Self {
+            trans_calls: 0,
+            join_calls: 0,
+            visits: HashMap::default(),
+            max_worklist_len: 0,
+        }
+    }
+}
+
+impl<Id> Stats<Id>
+where
+    Id: Copy + Eq + std::hash::Hash,
+{
+    /// The total number of worklist iterations
+    pub fn total_visits(&self) -> usize {
+        self.visits.values().sum()
+    }
+}
+
+/// An observer is notified of what the solver does as it runs. All callbacks
+/// default to doing nothing, so an implementation only needs to provide the
+/// ones it cares about.
+pub trait Observer<N: Node, F: Fact> {
+    /// Called when `id` is taken off the worklist, before its facts are
+    /// recomputed
+    fn node_visited(&mut self, _id: N::NodeId) {}
+
+    /// Called when the transformed fact for `id` differs from its previous
+    /// value. In a forwards problem, `old` and `new` are `after` facts.
+    fn fact_changed(&mut self, _id: N::NodeId, _old: &F, _new: &F) {}
+
+    /// Called when `id` is pushed onto the worklist because one of the nodes
+    /// it depends on changed. `len` is the length of the worklist after the
+    /// push.
+    fn node_enqueued(&mut self, _id: N::NodeId, _len: usize) {}
+
+    /// Called once the solver has reached a fixpoint
+    fn finished(&mut self, _stats: &Stats<N::NodeId>) {}
+}
+
+/// The unit observer ignores every event
+impl<N: Node, F: Fact> Observer<N, F> for () {}
+
+/// The observer for every solve which isn't given one, which logs its events
+/// with the `log` feature, and ignores them without it
+#[cfg(feature = "log")]
+pub(crate) use self::LogObserver as DefaultObserver;
+
+#[cfg(not(feature = "log"))]
+pub(crate) struct DefaultObserver;
+
+#[cfg(not(feature = "log"))]
+impl<N: Node, F: Fact> Observer<N, F> for DefaultObserver {}
+
+/// An observer which forwards every event to the `log` crate. Individual
+/// events are logged at the `trace` level, and the final counts at the `debug`
+/// level.
+#[cfg(feature = "log")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LogObserver;
+
+#[cfg(feature = "log")]
+impl<N, F> Observer<N, F> for LogObserver
+where
+    N: Node,
+    N::NodeId: std::fmt::Debug,
+    F: Fact,
+{
+    fn node_visited(&mut self, id: N::NodeId) {
+        log::trace!("visit {:?}", id);
+    }
+
+    fn fact_changed(&mut self, id: N::NodeId, _old: &F, _new: &F) {
+        log::trace!("changed {:?}", id);
+    }
+
+    fn node_enqueued(&mut self, id: N::NodeId, len: usize) {
+        log::trace!("enqueue {:?} (worklist length {})", id, len);
+    }
+
+    fn finished(&mut self, stats: &Stats<N::NodeId>) {
+        log::debug!(
+            "solved in {} visits: {} trans calls, {} join calls, max worklist length {}",
+            stats.total_visits(),
+            stats.trans_calls,
+            stats.join_calls,
+            stats.max_worklist_len
+        );
+    }
+}
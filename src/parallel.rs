@@ -0,0 +1,352 @@
+//! Solving a graph on many threads at once, behind the `rayon` feature.
+//!
+//! The graph is split into its strongly connected components, which only
+//! depend on each other in one direction. Each component is solved to a fixed
+//! point on a single thread with an ordinary worklist, starting from the final
+//! facts of the components it joins facts from, and is scheduled on the
+//! `rayon` thread pool as soon as the last of those is done. Components which
+//! don't depend on each other are solved at the same time, however far apart
+//! they are.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use super::observe::{DefaultObserver, Observer, Stats};
+use super::problem::Problem;
+use super::{Fact, Graph, Node, NodeInfo};
+use super::{HashMap, HashSet};
+
+/// Solve `graph` by splitting it into strongly connected components, and
+/// solving each component in parallel with the others as soon as every
+/// component it joins facts from is done. Each component is solved to a fixed
+/// point on its own, so the result is the same least fixed point the worklist
+/// solver finds, as long as `trans` is monotone.
+///
+/// ```plain
+/// pending(c) = number of components c joins facts from
+/// solve every c with pending(c) = 0, and when c is done, take 1 from the
+///     pending count of every component which joins facts from c
+/// ```
+pub(crate) fn solve<F, N, G, Trans, Join, Sort>(
+    graph: &G,
+    first_fact: &NodeInfo<F>,
+    init_fact: &NodeInfo<F>,
+    trans: &Trans,
+    join: &Join,
+) -> HashMap<N::NodeId, NodeInfo<F>>
+where
+    F: Fact + Send + Sync,
+    N: Node + Sync,
+    N::NodeId: std::fmt::Debug + Send + Sync,
+    G: Graph<N> + Sync,
+    Trans: Fn(&N, F) -> F + Sync,
+    Join: Fn(Vec<F>) -> F + Sync,
+    Sort: Problem<F, N, G>,
+{
+    let schedule = Schedule::new::<Sort>(graph, first_fact, init_fact, trans, join);
+
+    let start = &schedule;
+    rayon::scope(|scope| {
+        for (i, pending) in start.pending.iter().enumerate() {
+            if pending.load(Ordering::SeqCst) == 0 {
+                scope.spawn(move |scope| run::<F, N, G, Trans, Join, Sort>(scope, start, i));
+            }
+        }
+    });
+
+    let stats = schedule.stats.into_inner().unwrap();
+    Observer::<N, F>::finished(&mut DefaultObserver, &stats);
+
+    schedule.infos.into_inner().unwrap()
+}
+
+/// The components of a graph, which components wait on each other, and the
+/// facts of the components solved so far
+struct Schedule<'a, F, N, G, Trans, Join>
+where
+    F: Fact,
+    N: Node,
+{
+    graph: &'a G,
+    first_fact: &'a NodeInfo<F>,
+    init_fact: &'a NodeInfo<F>,
+    trans: &'a Trans,
+    join: &'a Join,
+
+    /// The strongly connected components of the graph
+    sccs: Vec<Vec<N::NodeId>>,
+
+    /// The components which join facts from each component
+    dependents: Vec<Vec<usize>>,
+
+    /// The number of components each component joins facts from which aren't
+    /// done yet
+    pending: Vec<AtomicUsize>,
+
+    /// The facts of every node of the components which are done
+    infos: Mutex<HashMap<N::NodeId, NodeInfo<F>>>,
+
+    /// The counts for the components which are done
+    stats: Mutex<Stats<N::NodeId>>,
+}
+
+impl<'a, F, N, G, Trans, Join> Schedule<'a, F, N, G, Trans, Join>
+where
+    F: Fact,
+    N: Node,
+    G: Graph<N>,
+{
+    fn new<Sort: Problem<F, N, G>>(
+        graph: &'a G,
+        first_fact: &'a NodeInfo<F>,
+        init_fact: &'a NodeInfo<F>,
+        trans: &'a Trans,
+        join: &'a Join,
+    ) -> Self {
+        let sccs = sccs::<F, N, G, Sort>(graph);
+        let mut component = HashMap::default();
+        for (i, scc) in sccs.iter().enumerate() {
+            for id in scc {
+                component.insert(*id, i);
+            }
+        }
+
+        let mut dependents = vec![Vec::new(); sccs.len()];
+        let mut pending = Vec::with_capacity(sccs.len());
+
+        for (i, scc) in sccs.iter().enumerate() {
+            let mut sources = HashSet::default();
+            for id in scc {
+                for source in Sort::get_joins(graph, *id) {
+                    let from = component[source];
+                    if from != i && sources.insert(from) {
+                        dependents[from].push(i);
+                    }
+                }
+            }
+
+            pending.push(AtomicUsize::new(sources.len()));
+        }
+
+        Self {
+            graph,
+            first_fact,
+            init_fact,
+            trans,
+            join,
+            sccs,
+            dependents,
+            pending,
+            infos: Mutex::new(HashMap::default()),
+            stats: Mutex::new(Stats::default()),
+        }
+    }
+}
+
+/// Solve the component `i`, whose sources are all done, then start every
+/// component which was only waiting on it
+fn run<'s, F, N, G, Trans, Join, Sort>(
+    scope: &rayon::Scope<'s>,
+    schedule: &'s Schedule<'s, F, N, G, Trans, Join>,
+    i: usize,
+) where
+    F: Fact + Send + Sync,
+    N: Node + Sync,
+    N::NodeId: std::fmt::Debug + Send + Sync,
+    G: Graph<N> + Sync,
+    Trans: Fn(&N, F) -> F + Sync,
+    Join: Fn(Vec<F>) -> F + Sync,
+    Sort: Problem<F, N, G>,
+{
+    let graph = schedule.graph;
+    let scc = &schedule.sccs[i];
+
+    // Copy out the facts the component joins, so the others aren't held up
+    // while it's solved
+    let sources: HashMap<_, _> = {
+        let infos = schedule.infos.lock().unwrap();
+        scc.iter()
+            .flat_map(|id| Sort::get_joins(graph, *id))
+            .filter_map(|source| Some((*source, infos.get(source)?.clone())))
+            .collect()
+    };
+
+    let mut stats = Stats::default();
+    let facts = solve_scc::<F, N, G, Trans, Join, Sort>(schedule, scc, &sources, &mut stats);
+    schedule.infos.lock().unwrap().extend(facts);
+
+    let mut total = schedule.stats.lock().unwrap();
+    total.trans_calls += stats.trans_calls;
+    total.join_calls += stats.join_calls;
+    total.visits.extend(stats.visits);
+    total.max_worklist_len = total.max_worklist_len.max(stats.max_worklist_len);
+    drop(total);
+
+    for dependent in schedule.dependents[i].iter().copied() {
+        if schedule.pending[dependent].fetch_sub(1, Ordering::SeqCst) == 1 {
+            scope.spawn(move |scope| run::<F, N, G, Trans, Join, Sort>(scope, schedule, dependent));
+        }
+    }
+}
+
+/// Solve the nodes of a single component, given the final facts for every
+/// node it joins facts from, and count the steps taken in `stats`
+fn solve_scc<F, N, G, Trans, Join, Sort>(
+    schedule: &Schedule<'_, F, N, G, Trans, Join>,
+    scc: &[N::NodeId],
+    done: &HashMap<N::NodeId, NodeInfo<F>>,
+    stats: &mut Stats<N::NodeId>,
+) -> HashMap<N::NodeId, NodeInfo<F>>
+where
+    F: Fact,
+    N: Node,
+    N::NodeId: std::fmt::Debug,
+    G: Graph<N>,
+    Trans: Fn(&N, F) -> F,
+    Join: Fn(Vec<F>) -> F,
+    Sort: Problem<F, N, G>,
+{
+    let Schedule {
+        graph,
+        first_fact,
+        init_fact,
+        trans,
+        join,
+        ..
+    } = *schedule;
+
+    // Every component logs its own steps, since they may be on other threads
+    let mut observer = DefaultObserver;
+
+    let first = Sort::get_first(graph);
+    let mut infos: HashMap<_, _> = scc
+        .iter()
+        .map(|id| {
+            let info = if *id == first { first_fact } else { init_fact };
+            (*id, info.clone())
+        })
+        .collect();
+
+    let mut worklist: VecDeque<_> = scc.iter().copied().collect();
+    let mut queued: HashSet<_> = scc.iter().copied().collect();
+    stats.max_worklist_len = worklist.len();
+
+    while let Some(id) = worklist.pop_front() {
+        queued.remove(&id);
+        Observer::<N, F>::node_visited(&mut observer, id);
+        *stats.visits.entry(id).or_insert(0) += 1;
+
+        let mut facts = Vec::new();
+        if id == first {
+            facts.push(Sort::get_joined_fact(first_fact).clone());
+        }
+
+        for next in Sort::get_joins(graph, id) {
+            let info = infos
+                .get(next)
+                .or_else(|| done.get(next))
+                .unwrap_or(init_fact);
+            facts.push(Sort::get_join_fact(info).clone());
+        }
+
+        let joined = join(facts);
+        let transd = trans(graph.get(id), joined.clone());
+        stats.join_calls += 1;
+        stats.trans_calls += 1;
+
+        let info = infos.get_mut(&id).unwrap();
+        let changed = Sort::get_join_fact(info) != &transd;
+        if changed {
+            Observer::<N, F>::fact_changed(&mut observer, id, Sort::get_join_fact(info), &transd);
+        }
+
+        Sort::assign(info, joined, transd);
+
+        if changed {
+            for dirty in Sort::get_nexts(graph, id) {
+                if infos.contains_key(dirty) && queued.insert(*dirty) {
+                    worklist.push_back(*dirty);
+                    Observer::<N, F>::node_enqueued(&mut observer, *dirty, worklist.len());
+                }
+            }
+
+            stats.max_worklist_len = stats.max_worklist_len.max(worklist.len());
+        }
+    }
+
+    infos
+}
+
+/// Find the strongly connected components of `graph` along the direction facts
+/// flow, with Tarjan's algorithm. Every component comes before the components
+/// which flow into it.
+fn sccs<F, N, G, Sort>(graph: &G) -> Vec<Vec<N::NodeId>>
+where
+    F: Fact,
+    N: Node,
+    G: Graph<N>,
+    Sort: Problem<F, N, G>,
+{
+    let mut index = HashMap::default();
+    let mut lowlink = HashMap::default();
+    let mut stack = Vec::new();
+    let mut on_stack = HashSet::default();
+    let mut sccs = Vec::new();
+
+    for root in graph.get_all_node_ids() {
+        if index.contains_key(root) {
+            continue;
+        }
+
+        let mut visits = vec![(*root, 0)];
+        index.insert(*root, index.len());
+        lowlink.insert(*root, index[root]);
+        stack.push(*root);
+        on_stack.insert(*root);
+
+        while let Some((id, i)) = visits.last().copied() {
+            match Sort::get_nexts(graph, id).get(i) {
+                Some(next) => {
+                    visits.last_mut().unwrap().1 += 1;
+
+                    if !index.contains_key(next) {
+                        index.insert(*next, index.len());
+                        lowlink.insert(*next, index[next]);
+                        stack.push(*next);
+                        on_stack.insert(*next);
+                        visits.push((*next, 0));
+                    } else if on_stack.contains(next) {
+                        let low = lowlink[&id].min(index[next]);
+                        lowlink.insert(id, low);
+                    }
+                }
+                None => {
+                    visits.pop();
+
+                    if let Some((parent, _)) = visits.last() {
+                        let low = lowlink[parent].min(lowlink[&id]);
+                        lowlink.insert(*parent, low);
+                    }
+
+                    if lowlink[&id] == index[&id] {
+                        let mut scc = Vec::new();
+
+                        while let Some(member) = stack.pop() {
+                            on_stack.remove(&member);
+                            scc.push(member);
+
+                            if member == id {
+                                break;
+                            }
+                        }
+
+                        sccs.push(scc);
+                    }
+                }
+            }
+        }
+    }
+
+    sccs
+}
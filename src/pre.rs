@@ -0,0 +1,281 @@
+//! Partial redundancy elimination by lazy code motion, as described by Knoop,
+//! Rüthing and Steffen.
+//!
+//! Lazy code motion finds where to compute every expression into a temporary,
+//! so that no path evaluates an expression more often than before, and so
+//! that every temporary lives as briefly as possible. It is made up of four
+//! dataflow problems, each of which is solved with an `Analyzer`:
+//!
+//! ```plain
+//! anticipated  backwards, must  in(b)  = union(use(b), out(b) - kill(b))
+//! available    forwards,  must  out(b) = union(anticipated.in(b), in(b)) - kill(b)
+//! postponable  forwards,  must  out(b) = union(earliest(b), in(b)) - use(b)
+//! used         backwards, may   in(b)  = union(use(b), out(b)) - latest(b)
+//! ```
+//!
+//! where `use(b)` gives the expressions evaluated in `b` before any of their
+//! operands are assigned, `kill(b)` gives the expressions with an operand
+//! assigned in `b`, and
+//!
+//! ```plain
+//! earliest(b) = anticipated.in(b) - available.in(b)
+//! latest(b)   = intersection(
+//!                   union(earliest(b), postponable.in(b)),
+//!                   union(use(b), not(intersection(
+//!                       union(earliest(s), postponable.in(s)) for s in succs(b)))))
+//! ```
+//!
+//! Computations are only ever inserted at the start of a block, so the
+//! placement is best when every critical edge has been split, and when every
+//! block holds a single statement. `run` takes care of the first for a
+//! `NodeGraph`.
+
+use std::hash::Hash;
+
+use super::analyses::{busy, Expressions, Located};
+use super::cfg::{Block, BlockId, NodeGraph};
+use super::lattice::{join_all, Must, PowerSet};
+use super::{Analyzer, Graph, HashMap, HashSet, Node};
+
+/// Where to compute expressions, and which evaluations to replace
+#[derive(Clone, Debug, PartialEq)]
+pub struct Placement<Id: Eq + Hash, E: Eq + Hash> {
+    /// The expressions to compute into their temporaries at the start of a
+    /// node. Nodes without insertions are left out.
+    pub insert: HashMap<Id, HashSet<E>>,
+
+    /// The expressions to compute into their temporaries on an edge of the
+    /// original graph, when it had to be split. Edges without insertions are
+    /// left out.
+    pub edge_insert: HashMap<(Id, Id), HashSet<E>>,
+
+    /// The expressions whose first evaluation in a node (before any of their
+    /// operands are assigned) should read the temporary instead. Nodes
+    /// without deletions are left out.
+    pub delete: HashMap<Id, HashSet<E>>,
+}
+
+/// Split the critical edges of `graph` and run lazy code motion on it.
+/// Insertions in the blocks put on critical edges are reported in
+/// `edge_insert`, and the new blocks are left in the graph.
+pub fn run<T>(graph: &mut NodeGraph<T>) -> Placement<BlockId, <Block<T> as Expressions>::Expr>
+where
+    T: Default,
+    Block<T>: Expressions + Node<NodeId = BlockId>,
+{
+    let split = graph.split_critical_edges();
+    let mut placement = lazy_code_motion(&*graph);
+
+    for (from, to, id) in split {
+        if let Some(exprs) = placement.insert.remove(&id) {
+            placement.edge_insert.insert((from, to), exprs);
+        }
+    }
+
+    placement
+}
+
+/// Run lazy code motion on `graph`, placing insertions at the start of nodes
+pub fn lazy_code_motion<N, G>(graph: &G) -> Placement<N::NodeId, N::Expr>
+where
+    N: Expressions + Located,
+    N::NodeId: std::fmt::Debug,
+    G: Graph<N>,
+{
+    let ids = graph.get_all_node_ids();
+    let local = Local::new(graph);
+
+    // Anticipated expressions
+    let anticipated = busy::solve(graph);
+    let anticipated: HashMap<_, _> = ids
+        .iter()
+        .map(|id| (*id, local.concrete(&anticipated[id].before)))
+        .collect();
+
+    // Available expressions, assuming every anticipated expression is
+    // computed as early as possible
+    let available = Analyzer::new_forward(
+        PowerSet::all(),
+        |node: &N, fact: PowerSet<N::Expr, Must>| {
+            let id = node.id();
+            local.update(&fact, &anticipated[&id], &local.kills[&id])
+        },
+        join_all,
+    )
+    .with_entry_fact(PowerSet::empty())
+    .solve(graph);
+
+    let earliest: HashMap<_, _> = ids
+        .iter()
+        .map(|id| {
+            let available = local.concrete(&available[id].before);
+            let earliest: HashSet<_> = anticipated[id].difference(&available).cloned().collect();
+            (*id, earliest)
+        })
+        .collect();
+
+    // Postponable expressions
+    let postponable = Analyzer::new_forward(
+        PowerSet::all(),
+        |node: &N, fact: PowerSet<N::Expr, Must>| {
+            let id = node.id();
+            local.update(&fact, &earliest[&id], &local.uses[&id])
+        },
+        join_all,
+    )
+    .with_entry_fact(PowerSet::empty())
+    .solve(graph);
+
+    // `union(earliest(b), postponable.in(b))` is needed for every block and
+    // all its successors
+    let frontier: HashMap<_, HashSet<_>> = ids
+        .iter()
+        .map(|id| {
+            let postponable = local.concrete(&postponable[id].before);
+            (*id, earliest[id].union(&postponable).cloned().collect())
+        })
+        .collect();
+
+    let latest: HashMap<_, HashSet<_>> = ids
+        .iter()
+        .map(|id| {
+            let blocked: HashSet<_> = local
+                .all
+                .iter()
+                .filter(|e| {
+                    local.uses[id].contains(*e)
+                        || graph
+                            .get_succs(*id)
+                            .iter()
+                            .any(|s| !frontier[s].contains(*e))
+                })
+                .cloned()
+                .collect();
+
+            (*id, frontier[id].intersection(&blocked).cloned().collect())
+        })
+        .collect();
+
+    // Used expressions
+    let used = Analyzer::new_backward_lattice(|node: &N, fact: PowerSet<N::Expr>| {
+        let id = node.id();
+        local.update(&fact, &local.uses[&id], &latest[&id])
+    })
+    .solve(graph);
+
+    let mut placement = Placement {
+        insert: HashMap::default(),
+        edge_insert: HashMap::default(),
+        delete: HashMap::default(),
+    };
+
+    for id in ids {
+        let used = local.concrete(&used[id].after);
+
+        let insert: HashSet<_> = latest[id].intersection(&used).cloned().collect();
+        if !insert.is_empty() {
+            placement.insert.insert(*id, insert);
+        }
+
+        let delete: HashSet<_> = local.uses[id]
+            .iter()
+            .filter(|e| !latest[id].contains(*e) || used.contains(*e))
+            .cloned()
+            .collect();
+
+        if !delete.is_empty() {
+            placement.delete.insert(*id, delete);
+        }
+    }
+
+    placement
+}
+
+/// The local properties of every node
+struct Local<Id, E> {
+    /// Every expression evaluated anywhere in the graph
+    all: HashSet<E>,
+
+    /// The expressions evaluated in a node before any of their operands are
+    /// assigned
+    uses: HashMap<Id, HashSet<E>>,
+
+    /// The expressions with an operand assigned in a node
+    kills: HashMap<Id, HashSet<E>>,
+}
+
+impl<Id, E> Local<Id, E>
+where
+    Id: Copy + Eq + Hash,
+    E: Clone + Eq + Hash,
+{
+    fn new<N, G>(graph: &G) -> Self
+    where
+        N: Expressions<NodeId = Id, Expr = E>,
+        G: Graph<N>,
+    {
+        let ids = graph.get_all_node_ids();
+
+        let mut all = HashSet::default();
+        for id in ids {
+            let node = graph.get(*id);
+            for stmt in 0..node.stmt_count() {
+                all.extend(node.exprs(stmt).iter().cloned());
+            }
+        }
+
+        let mut uses = HashMap::default();
+        let mut kills = HashMap::default();
+
+        for id in ids {
+            let node = graph.get(*id);
+            let mut used = HashSet::default();
+            let mut killed = HashSet::default();
+
+            for stmt in 0..node.stmt_count() {
+                for expr in node.exprs(stmt) {
+                    if !killed.contains(expr) {
+                        used.insert(expr.clone());
+                    }
+                }
+
+                for var in node.defs(stmt) {
+                    for expr in all.iter() {
+                        if N::operands(expr).contains(var) {
+                            killed.insert(expr.clone());
+                        }
+                    }
+                }
+            }
+
+            uses.insert(*id, used);
+            kills.insert(*id, killed);
+        }
+
+        Self { all, uses, kills }
+    }
+
+    /// Turn the set of everything into the set of every expression in the
+    /// graph
+    fn concrete<K: Clone>(&self, set: &PowerSet<E, K>) -> HashSet<E> {
+        match set.as_set() {
+            Some(set) => set.clone(),
+            None => self.all.clone(),
+        }
+    }
+
+    /// ```plain
+    /// union(gen, fact) - kill
+    /// ```
+    fn update<K: Clone>(
+        &self,
+        fact: &PowerSet<E, K>,
+        gen: &HashSet<E>,
+        kill: &HashSet<E>,
+    ) -> PowerSet<E, K> {
+        let mut fact = self.concrete(fact);
+        fact.extend(gen.iter().cloned());
+        fact.retain(|e| !kill.contains(e));
+        fact.into_iter().collect()
+    }
+}
@@ -21,6 +21,10 @@ pub trait Problem<F: Fact, N: Node, G: Graph<N>>: private::Sealed {
     /// transformed. This also gives the fact which will be `join`ed with other
     /// facts.
     fn get_join_fact(info: &NodeInfo<F>) -> &F;
+
+    /// Get the fact which is computed by joining. This is the fact that was
+    /// passed to `trans`. In a forwards problem, this is the `before` fact.
+    fn get_joined_fact(info: &NodeInfo<F>) -> &F;
 }
 
 /// A forwards problem finds information which holds on all paths from `enter`
@@ -53,6 +57,10 @@ where
     fn get_join_fact(info: &NodeInfo<F>) -> &F {
         &info.after
     }
+
+    fn get_joined_fact(info: &NodeInfo<F>) -> &F {
+        &info.before
+    }
 }
 
 /// A backwards problem finds information which holds on all paths from `n` to
@@ -85,6 +93,10 @@ where
     fn get_join_fact(info: &NodeInfo<F>) -> &F {
         &info.before
     }
+
+    fn get_joined_fact(info: &NodeInfo<F>) -> &F {
+        &info.after
+    }
 }
 
 mod private {
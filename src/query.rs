@@ -0,0 +1,134 @@
+use std::collections::VecDeque;
+
+use super::analyze::Analyzer;
+use super::observe::{DefaultObserver, Observer, Stats};
+use super::problem::Problem;
+use super::HashSet;
+use super::{Fact, Graph, Node, NodeInfo};
+
+/// A demand driven solver, which finds the facts of single nodes without
+/// solving the whole graph.
+///
+/// Asking for the facts of a node solves only the nodes they depend on: its
+/// predecessors in a forwards problem, or its successors in a backwards
+/// problem, and theirs in turn. Those nodes are solved together until nothing
+/// changes, so cycles work as usual, and their facts are remembered for later
+/// queries.
+pub struct Query<'a, F, N, G, Trans, Join, Sort>
+where
+    F: Fact,
+    N: Node,
+    G: Graph<N>,
+    Trans: FnMut(&N, F) -> F,
+    Join: FnMut(Vec<F>) -> F,
+{
+    analyzer: &'a mut Analyzer<F, N, G, Trans, Join, Sort>,
+    graph: &'a G,
+
+    /// Nodes whose facts are final
+    solved: HashSet<N::NodeId>,
+    stats: Stats<N::NodeId>,
+}
+
+impl<'a, F, N, G, Trans, Join, Sort> Query<'a, F, N, G, Trans, Join, Sort>
+where
+    F: Fact,
+    N: Node,
+    N::NodeId: std::fmt::Debug,
+    G: Graph<N>,
+    Trans: FnMut(&N, F) -> F,
+    Join: FnMut(Vec<F>) -> F,
+    Sort: Problem<F, N, G>,
+{
+    pub(crate) fn new(
+        analyzer: &'a mut Analyzer<F, N, G, Trans, Join, Sort>,
+        graph: &'a G,
+    ) -> Self {
+        analyzer.reset(graph);
+
+        Self {
+            analyzer,
+            graph,
+            solved: HashSet::default(),
+            stats: Stats::default(),
+        }
+    }
+
+    /// Get the facts for the node `id`
+    pub fn get(&mut self, id: N::NodeId) -> &NodeInfo<F> {
+        self.solve(id);
+        &self.analyzer.infos()[&id]
+    }
+
+    /// Get the fact which holds before the node `id`
+    pub fn before(&mut self, id: N::NodeId) -> &F {
+        &self.get(id).before
+    }
+
+    /// Get the fact which holds after the node `id`
+    pub fn after(&mut self, id: N::NodeId) -> &F {
+        &self.get(id).after
+    }
+
+    /// Get the nodes which have been solved to answer the queries so far
+    pub fn solved(&self) -> impl Iterator<Item = &N::NodeId> {
+        self.solved.iter()
+    }
+
+    /// Get the counts for the steps taken so far
+    pub fn stats(&self) -> &Stats<N::NodeId> {
+        &self.stats
+    }
+
+    /// Solve `id` and every node it depends on which hasn't been solved yet
+    fn solve(&mut self, id: N::NodeId) {
+        if self.solved.contains(&id) {
+            return;
+        }
+
+        // Find the nodes whose facts flow into `id`, stopping at the ones
+        // solved by earlier queries
+        let mut cone = vec![id];
+        let mut seen = HashSet::default();
+        seen.insert(id);
+
+        let mut i = 0;
+        while i < cone.len() {
+            for next in self.analyzer.joins(self.graph, cone[i]) {
+                if !self.solved.contains(next) && seen.insert(*next) {
+                    cone.push(*next);
+                }
+            }
+
+            i += 1;
+        }
+
+        // Start from the nodes furthest away from `id`, so that facts mostly
+        // flow towards it in order
+        let mut worklist: VecDeque<_> = cone.iter().rev().copied().collect();
+        self.stats.max_worklist_len = self.stats.max_worklist_len.max(worklist.len());
+        let mut observer = DefaultObserver;
+
+        while let Some(next) = worklist.pop_front() {
+            let changed = self
+                .analyzer
+                .update(self.graph, next, &mut observer, &mut self.stats);
+
+            if !changed {
+                continue;
+            }
+
+            for dirty in self.analyzer.nexts(self.graph, next) {
+                if seen.contains(dirty) && !worklist.contains(dirty) {
+                    worklist.push_back(*dirty);
+                    Observer::<N, F>::node_enqueued(&mut observer, *dirty, worklist.len());
+                }
+            }
+
+            self.stats.max_worklist_len = self.stats.max_worklist_len.max(worklist.len());
+        }
+
+        self.solved.extend(cone);
+        Observer::<N, F>::finished(&mut observer, &self.stats);
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::VecDeque;
+
+use super::analyze::Analyzer;
+use super::observe::{DefaultObserver, Observer, Stats};
+use super::problem::Problem;
+use super::HashMap;
+use super::{Fact, Graph, Node, NodeInfo};
+
+/// A single step of the solver, in which one node was taken off the worklist
+/// and had its facts recomputed
+#[derive(Clone, Debug, PartialEq)]
+pub struct Step<F: Fact, Id> {
+    /// The node which was visited
+    pub id: Id,
+
+    /// The fact produced by `join`ing the facts of the node's predecessors (in
+    /// a forwards problem) or successors (in a backwards problem)
+    pub joined: F,
+
+    /// The fact produced by `trans`forming `joined`
+    pub transd: F,
+
+    /// Whether `transd` differs from the previous transformed fact for this
+    /// node
+    pub changed: bool,
+
+    /// The worklist after this step, in the order nodes will be visited
+    pub worklist: Vec<Id>,
+}
+
+/// An iterator over the steps taken by the solver. The solve finishes when
+/// the iterator is exhausted, but it can be stopped or inspected at any point.
+pub struct Steps<'a, F, N, G, Trans, Join, Sort>
+where
+    F: Fact,
+    N: Node,
+    G: Graph<N>,
+    Trans: FnMut(&N, F) -> F,
+    Join: FnMut(Vec<F>) -> F,
+{
+    analyzer: &'a mut Analyzer<F, N, G, Trans, Join, Sort>,
+    graph: &'a G,
+    worklist: VecDeque<N::NodeId>,
+    stats: Stats<N::NodeId>,
+}
+
+impl<'a, F, N, G, Trans, Join, Sort> Steps<'a, F, N, G, Trans, Join, Sort>
+where
+    F: Fact,
+    N: Node,
+    N::NodeId: std::fmt::Debug,
+    G: Graph<N>,
+    Trans: FnMut(&N, F) -> F,
+    Join: FnMut(Vec<F>) -> F,
+    Sort: Problem<F, N, G>,
+{
+    pub(crate) fn new(
+        analyzer: &'a mut Analyzer<F, N, G, Trans, Join, Sort>,
+        graph: &'a G,
+    ) -> Self {
+        let mut stats = Stats::default();
+        let worklist = analyzer.start(graph, &mut stats);
+
+        Self {
+            analyzer,
+            graph,
+            worklist,
+            stats,
+        }
+    }
+
+    /// Get the facts computed so far. Nodes which have not been visited yet
+    /// may be missing.
+    pub fn infos(&self) -> &HashMap<N::NodeId, NodeInfo<F>> {
+        self.analyzer.infos()
+    }
+
+    /// Get the nodes which are still waiting to be visited
+    pub fn worklist(&self) -> impl Iterator<Item = &N::NodeId> {
+        self.worklist.iter()
+    }
+
+    /// Get the counts for the steps taken so far
+    pub fn stats(&self) -> &Stats<N::NodeId> {
+        &self.stats
+    }
+
+    /// Run the remaining steps and return the final facts, just like
+    /// `Analyzer::solve` would
+    pub fn finish(mut self) -> HashMap<N::NodeId, NodeInfo<F>> {
+        while self.step().is_some() {}
+
+        self.analyzer.take_infos()
+    }
+
+    /// Visit the next node on the worklist, returning it and whether its
+    /// transformed fact changed
+    fn step(&mut self) -> Option<(N::NodeId, bool)> {
+        let mut observer = DefaultObserver;
+
+        let id = self.worklist.pop_front()?;
+        let changed = self.analyzer.visit(
+            self.graph,
+            id,
+            &mut self.worklist,
+            &mut observer,
+            &mut self.stats,
+        );
+
+        if self.worklist.is_empty() {
+            Observer::<N, F>::finished(&mut observer, &self.stats);
+        }
+
+        Some((id, changed))
+    }
+}
+
+impl<'a, F, N, G, Trans, Join, Sort> Iterator for Steps<'a, F, N, G, Trans, Join, Sort>
+where
+    F: Fact,
+    N: Node,
+    N::NodeId: std::fmt::Debug,
+    G: Graph<N>,
+    Trans: FnMut(&N, F) -> F,
+    Join: FnMut(Vec<F>) -> F,
+    Sort: Problem<F, N, G>,
+{
+    type Item = Step<F, N::NodeId>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (id, changed) = self.step()?;
+        let info = &self.analyzer.infos()[&id];
+
+        Some(Step {
+            id,
+            joined: Sort::get_joined_fact(info).clone(),
+            transd: Sort::get_join_fact(info).clone(),
+            changed,
+            worklist: self.worklist.iter().copied().collect(),
+        })
+    }
+}
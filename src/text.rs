@@ -0,0 +1,506 @@
+//! A plain text format for control flow graphs.
+//!
+//! Every non-empty line defines one block:
+//!
+//! ```plain
+//! <id> [entry] [exit] [-> <id>, <id>, ...] [: <payload>]
+//! ```
+//!
+//! Ids are unsigned integers. `entry` and `exit` mark the entry and exit
+//! blocks; if no block is marked, the first block is the entry and the last
+//! block is the exit. Edges are only written on the block they start from,
+//! and may point to blocks defined further down. The payload is everything
+//! after the `:`, with surrounding whitespace removed, and is not interpreted
+//! in any way. Lines whose first non-blank character is `#` are comments.
+//!
+//! ```plain
+//! # a = 0; loop { b = a }
+//! 1 entry -> 2    : a = 0
+//! 2 -> 2, 3       : b = a
+//! 3 exit
+//! ```
+//!
+//! `parse_statements` reads payloads as statements in the language from
+//! `cfg`, separated by `;`. Variables are named by identifiers, and declared
+//! with `var`:
+//!
+//! ```plain
+//! 1 -> 2 : var x; a = 5
+//! 2      : x = a; b = -1
+//! ```
+
+use std::fmt;
+
+use super::cfg::{BinOp, Block, BlockId, Expr, NodeGraph, Statement, Variable};
+use super::Graph;
+
+/// The reason a graph couldn't be parsed
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseErrorKind {
+    /// There were no blocks
+    Empty,
+
+    /// Something other than a block id was found where an id was expected
+    ExpectedId,
+
+    /// An id was too large to fit in a `BlockId`
+    IdTooLarge,
+
+    /// An unexpected character was found
+    Unexpected(char),
+
+    /// The block was already defined
+    DuplicateBlock(BlockId),
+
+    /// An edge points to a block which is never defined
+    UnknownBlock(BlockId),
+
+    /// More than one block is marked `entry`
+    DuplicateEntry,
+
+    /// More than one block is marked `exit`
+    DuplicateExit,
+
+    /// The payload of a block was rejected
+    Payload(String),
+}
+
+/// An error found while parsing, along with where it was found. Lines and
+/// columns are counted from 1, and columns count characters.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: ", self.line, self.column)?;
+
+        match &self.kind {
+            ParseErrorKind::Empty => write!(f, "no blocks"),
+            ParseErrorKind::ExpectedId => write!(f, "expected a block id"),
+            ParseErrorKind::IdTooLarge => write!(f, "block id is too large"),
+            ParseErrorKind::Unexpected(c) => write!(f, "unexpected {:?}", c),
+            ParseErrorKind::DuplicateBlock(id) => write!(f, "block {} is already defined", id),
+            ParseErrorKind::UnknownBlock(id) => write!(f, "block {} is never defined", id),
+            ParseErrorKind::DuplicateEntry => write!(f, "more than one entry block"),
+            ParseErrorKind::DuplicateExit => write!(f, "more than one exit block"),
+            ParseErrorKind::Payload(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// An error found while interpreting a payload. `offset` is the number of
+/// characters into the payload where the error was found.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayloadError {
+    pub offset: usize,
+    pub message: String,
+}
+
+/// Parse a graph in the text format. The payload of every block is its data.
+pub fn parse(src: &str) -> Result<NodeGraph<String>, ParseError> {
+    parse_with(src, |payload| Ok(payload.to_string()))
+}
+
+/// Parse a graph in the text format, using `payload` to turn the payload of
+/// every block into its data. Blocks without a payload are given an empty
+/// one.
+pub fn parse_with<T, Payload>(src: &str, mut payload: Payload) -> Result<NodeGraph<T>, ParseError>
+where
+    Payload: FnMut(&str) -> Result<T, PayloadError>,
+{
+    let mut blocks: Vec<Block<T>> = Vec::new();
+    let mut edges = Vec::new();
+    let mut entry = None;
+    let mut exit = None;
+
+    for (i, line) in src.lines().enumerate() {
+        let mut cursor = Cursor::new(i + 1, line);
+        cursor.skip_blanks();
+
+        if matches!(cursor.peek(), None | Some('#')) {
+            continue;
+        }
+
+        let (id, pos) = cursor.id()?;
+        if blocks.iter().any(|b| b.id == id) {
+            return Err(pos.error(ParseErrorKind::DuplicateBlock(id)));
+        }
+
+        // Markers
+        loop {
+            cursor.skip_blanks();
+            let pos = cursor.pos();
+
+            if cursor.eat_word("entry") {
+                if entry.replace(id).is_some() {
+                    return Err(pos.error(ParseErrorKind::DuplicateEntry));
+                }
+            } else if cursor.eat_word("exit") {
+                if exit.replace(id).is_some() {
+                    return Err(pos.error(ParseErrorKind::DuplicateExit));
+                }
+            } else {
+                break;
+            }
+        }
+
+        // Edges
+        if cursor.eat("->") {
+            loop {
+                cursor.skip_blanks();
+                let (to, pos) = cursor.id()?;
+                edges.push((id, to, pos));
+
+                cursor.skip_blanks();
+                if !cursor.eat(",") {
+                    break;
+                }
+            }
+        }
+
+        // Payload
+        cursor.skip_blanks();
+        let (text, pos) = if cursor.eat(":") {
+            cursor.skip_blanks();
+            let pos = cursor.pos();
+            (cursor.rest().trim_end(), pos)
+        } else {
+            cursor.end()?;
+            ("", cursor.pos())
+        };
+
+        let data = payload(text).map_err(|e| {
+            let pos = Pos {
+                column: pos.column + e.offset,
+                ..pos
+            };
+
+            pos.error(ParseErrorKind::Payload(e.message))
+        })?;
+
+        blocks.push(Block::new(id, data));
+    }
+
+    let mut blocks = blocks.into_iter();
+    let mut graph = match blocks.next() {
+        Some(block) => NodeGraph::new(block),
+        None => {
+            let line = src.lines().count().max(1);
+            return Err(Pos { line, column: 1 }.error(ParseErrorKind::Empty));
+        }
+    };
+
+    for block in blocks {
+        graph.insert(block);
+    }
+
+    for (from, to, pos) in edges {
+        if !graph.contains(to) {
+            return Err(pos.error(ParseErrorKind::UnknownBlock(to)));
+        }
+
+        graph.add_edge(from, to);
+    }
+
+    let ids = graph.get_all_node_ids();
+    let first = ids[0];
+    let last = ids[ids.len() - 1];
+    graph.set_entry(entry.unwrap_or(first));
+    graph.set_exit(exit.unwrap_or(last));
+
+    Ok(graph)
+}
+
+/// A graph of statements, along with the names of its variables
+#[derive(Clone, Debug)]
+pub struct StatementGraph {
+    pub graph: NodeGraph<Vec<Statement>>,
+
+    /// The name of every variable, indexed by its id
+    pub names: Vec<String>,
+}
+
+impl StatementGraph {
+    /// Get the name of a variable
+    pub fn name(&self, var: Variable) -> &str {
+        &self.names[var.0]
+    }
+}
+
+/// Parse a graph in the text format whose payloads are `;`-separated
+/// statements. Variables are numbered in the order they first appear.
+pub fn parse_statements(src: &str) -> Result<StatementGraph, ParseError> {
+    let mut names = Vec::new();
+    let graph = parse_with(src, |payload| statements(payload, &mut names))?;
+
+    Ok(StatementGraph { graph, names })
+}
+
+/// Parse a list of statements, adding new variables to `names`
+fn statements(payload: &str, names: &mut Vec<String>) -> Result<Vec<Statement>, PayloadError> {
+    let mut res = Vec::new();
+    let mut offset = 0;
+
+    for stmt in payload.split(';') {
+        let tokens = tokenize(stmt, offset)?;
+        offset += stmt.chars().count() + 1;
+
+        let mut var = |name: &str| match names.iter().position(|n| n == name) {
+            Some(i) => Variable(i),
+            None => {
+                names.push(name.to_string());
+                Variable(names.len() - 1)
+            }
+        };
+
+        let stmt = match tokens.as_slice() {
+            [] => continue,
+            [(_, "var"), (_, name)] if is_ident(name) => Statement::Declare(var(name)),
+            [(_, name), (_, "="), (at, value)] if is_ident(name) => {
+                if is_ident(value) {
+                    Statement::VarAssign(var(name), var(value))
+                } else {
+                    let value = value.parse().map_err(|_| PayloadError {
+                        offset: *at,
+                        message: format!("{} is not a valid constant", value),
+                    })?;
+
+                    Statement::ConstAssign(var(name), value)
+                }
+            }
+            [(_, name), (_, "="), (_, lhs), (at, op), (_, rhs)]
+                if is_ident(name) && is_ident(lhs) && is_ident(rhs) =>
+            {
+                let op = match *op {
+                    "+" => BinOp::Add,
+                    "-" => BinOp::Sub,
+                    "*" => BinOp::Mul,
+                    _ => {
+                        return Err(PayloadError {
+                            offset: *at,
+                            message: format!("unknown operator `{}`", op),
+                        })
+                    }
+                };
+
+                let name = var(name);
+                let args = [var(lhs), var(rhs)];
+                Statement::ExprAssign(name, Expr { op, args })
+            }
+            [(at, _), ..] => {
+                return Err(PayloadError {
+                    offset: *at,
+                    message: "expected `var x`, `x = 5`, `x = a` or `x = a + b`".to_string(),
+                })
+            }
+        };
+
+        res.push(stmt);
+    }
+
+    Ok(res)
+}
+
+/// Split a statement into identifiers, numbers, `=` and operators, along with
+/// their offset in characters. A `-` directly followed by a digit starts a
+/// number.
+fn tokenize(stmt: &str, offset: usize) -> Result<Vec<(usize, &str)>, PayloadError> {
+    let mut tokens = Vec::new();
+    let mut chars = stmt.char_indices().enumerate().peekable();
+
+    while let Some((i, (start, c))) = chars.next() {
+        let at = offset + i;
+
+        if c.is_whitespace() {
+            continue;
+        }
+
+        let negative = c == '-' && matches!(chars.peek(), Some((_, (_, d))) if d.is_ascii_digit());
+
+        if matches!(c, '=' | '+' | '-' | '*') && !negative {
+            tokens.push((at, &stmt[start..start + 1]));
+            continue;
+        }
+
+        if !(c.is_alphanumeric() || c == '_' || negative) {
+            return Err(PayloadError {
+                offset: at,
+                message: format!("unexpected {:?}", c),
+            });
+        }
+
+        let mut end = start + c.len_utf8();
+        while let Some((_, (j, c))) = chars.peek() {
+            if !(c.is_alphanumeric() || *c == '_') {
+                break;
+            }
+
+            end = j + c.len_utf8();
+            chars.next();
+        }
+
+        tokens.push((at, &stmt[start..end]));
+    }
+
+    Ok(tokens)
+}
+
+/// Identifiers start with a letter or `_`, and `var` is reserved
+fn is_ident(s: &str) -> bool {
+    let first = s.chars().next();
+    s != "var" && matches!(first, Some(c) if c.is_alphabetic() || c == '_')
+}
+
+/// Write a graph in the text format, using `Display` for the payloads. The
+/// output can be read back with `parse` as long as no payload contains a line
+/// break.
+pub fn write<T: fmt::Display>(graph: &NodeGraph<T>) -> String {
+    let mut out = String::new();
+
+    for block in graph.blocks() {
+        out.push_str(&block.id.to_string());
+
+        if block.id == graph.get_entry() {
+            out.push_str(" entry");
+        }
+
+        if block.id == graph.get_exit() {
+            out.push_str(" exit");
+        }
+
+        if !block.succs.is_empty() {
+            let succs: Vec<_> = block.succs.iter().map(|id| id.to_string()).collect();
+            out.push_str(" -> ");
+            out.push_str(&succs.join(", "));
+        }
+
+        let data = block.data.to_string();
+        if !data.is_empty() {
+            out.push_str(" : ");
+            out.push_str(&data);
+        }
+
+        out.push('\n');
+    }
+
+    out
+}
+
+/// A position in the source
+#[derive(Clone, Copy)]
+struct Pos {
+    line: usize,
+    column: usize,
+}
+
+impl Pos {
+    fn error(self, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: self.column,
+            kind,
+        }
+    }
+}
+
+/// A cursor over a single line
+struct Cursor<'a> {
+    line: usize,
+    column: usize,
+    rest: &'a str,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(line: usize, src: &'a str) -> Self {
+        Self {
+            line,
+            column: 1,
+            rest: src,
+        }
+    }
+
+    fn pos(&self) -> Pos {
+        Pos {
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest.chars().next()
+    }
+
+    fn bump(&mut self, bytes: usize) {
+        self.column += self.rest[..bytes].chars().count();
+        self.rest = &self.rest[bytes..];
+    }
+
+    fn skip_blanks(&mut self) {
+        let len = self.rest.len() - self.rest.trim_start().len();
+        self.bump(len);
+    }
+
+    /// Consume `s` if the rest of the line starts with it
+    fn eat(&mut self, s: &str) -> bool {
+        if self.rest.starts_with(s) {
+            self.bump(s.len());
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consume `word` if it is the next whole word
+    fn eat_word(&mut self, word: &str) -> bool {
+        if !self.rest.starts_with(word) {
+            return false;
+        }
+
+        match self.rest[word.len()..].chars().next() {
+            Some(c) if c.is_alphanumeric() || c == '_' => false,
+            _ => self.eat(word),
+        }
+    }
+
+    fn id(&mut self) -> Result<(BlockId, Pos), ParseError> {
+        let pos = self.pos();
+        let len = self
+            .rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(self.rest.len());
+
+        if len == 0 {
+            return Err(pos.error(ParseErrorKind::ExpectedId));
+        }
+
+        let id = self.rest[..len]
+            .parse()
+            .map_err(|_| pos.error(ParseErrorKind::IdTooLarge))?;
+
+        self.bump(len);
+        Ok((BlockId(id), pos))
+    }
+
+    /// Take the rest of the line
+    fn rest(&mut self) -> &'a str {
+        let rest = self.rest;
+        self.bump(rest.len());
+        rest
+    }
+
+    /// Make sure there is nothing left on the line
+    fn end(&mut self) -> Result<(), ParseError> {
+        self.skip_blanks();
+
+        match self.peek() {
+            Some(c) => Err(self.pos().error(ParseErrorKind::Unexpected(c))),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::VecDeque;
+
+use super::analyze::Analyzer;
+use super::observe::{DefaultObserver, Observer, Stats};
+use super::problem::Problem;
+use super::{Fact, Graph, HashMap, Node, NodeInfo};
+
+/// A tuple of mutable references to between two and six `Analyzer`s which go
+/// in the same direction over the same graph, to be solved together by
+/// `solve_together`
+pub trait Analyses<N: Node, G: Graph<N>>: private::Sealed {
+    /// A tuple with the facts for every analysis, in the same order
+    type Results;
+
+    /// The number of analyses
+    #[doc(hidden)]
+    const COUNT: usize;
+
+    /// Reset every analysis and create the initial worklist
+    #[doc(hidden)]
+    fn start(&mut self, graph: &G, stats: &mut Stats<N::NodeId>) -> VecDeque<N::NodeId>;
+
+    /// Recompute the facts of one analysis for the node `id`. Returns whether
+    /// the transformed fact changed.
+    #[doc(hidden)]
+    fn update(
+        &mut self,
+        graph: &G,
+        index: usize,
+        id: N::NodeId,
+        stats: &mut Stats<N::NodeId>,
+    ) -> bool;
+
+    /// Get the nodes which depend on the facts of `id`
+    #[doc(hidden)]
+    fn nexts<'g>(&self, graph: &'g G, id: N::NodeId) -> &'g [N::NodeId];
+
+    /// Tell the default observer that `id` was put back on the worklist
+    #[doc(hidden)]
+    fn node_enqueued(&self, id: N::NodeId, len: usize);
+
+    /// Tell the default observer that every analysis reached a fixpoint
+    #[doc(hidden)]
+    fn finished(&self, stats: &Stats<N::NodeId>);
+
+    /// Take the facts computed by every analysis
+    #[doc(hidden)]
+    fn finish(self) -> Self::Results;
+}
+
+/// Solve several problems going in the same direction in a single pass over
+/// `graph`, as if they were one problem over the product of their facts. Each
+/// node remembers which of the problems changed for the nodes it depends on,
+/// so only those are recomputed when it is visited.
+///
+/// ```ignore
+/// let mut reaching = Analyzer::new_forward(...);
+/// let mut available = Analyzer::new_forward(...);
+/// let (reaching, available) = creek::solve_together(&graph, (&mut reaching, &mut available));
+/// ```
+pub fn solve_together<N, G, A>(graph: &G, mut analyses: A) -> A::Results
+where
+    N: Node,
+    G: Graph<N>,
+    A: Analyses<N, G>,
+{
+    let mut stats = Stats::default();
+    let mut worklist = analyses.start(graph, &mut stats);
+
+    // Which analyses need to be recomputed for each node on the worklist
+    let all = (1 << A::COUNT) - 1;
+    let mut dirty: HashMap<N::NodeId, u8> = worklist.iter().map(|id| (*id, all)).collect();
+
+    while let Some(id) = worklist.pop_front() {
+        let mask = dirty.remove(&id).unwrap_or(0);
+
+        for index in 0..A::COUNT {
+            if mask & (1 << index) == 0 || !analyses.update(graph, index, id, &mut stats) {
+                continue;
+            }
+
+            for next in analyses.nexts(graph, id) {
+                let bits = dirty.entry(*next).or_insert(0);
+                if *bits == 0 {
+                    worklist.push_back(*next);
+                    analyses.node_enqueued(*next, worklist.len());
+                }
+
+                *bits |= 1 << index;
+            }
+        }
+
+        stats.max_worklist_len = stats.max_worklist_len.max(worklist.len());
+    }
+
+    analyses.finished(&stats);
+    analyses.finish()
+}
+
+macro_rules! analyses {
+    ($count:expr; $($index:tt: $F:ident $Trans:ident $Join:ident),+) => {
+        impl<'a, N, G, Sort, $($F, $Trans, $Join),+> private::Sealed
+            for ($(&'a mut Analyzer<$F, N, G, $Trans, $Join, Sort>,)+)
+        where
+            N: Node,
+            G: Graph<N>,
+            $(
+                $F: Fact,
+                $Trans: FnMut(&N, $F) -> $F,
+                $Join: FnMut(Vec<$F>) -> $F,
+            )+
+        {
+        }
+
+        impl<'a, N, G, Sort, $($F, $Trans, $Join),+> Analyses<N, G>
+            for ($(&'a mut Analyzer<$F, N, G, $Trans, $Join, Sort>,)+)
+        where
+            N: Node,
+            N::NodeId: std::fmt::Debug,
+            G: Graph<N>,
+            $(
+                $F: Fact,
+                $Trans: FnMut(&N, $F) -> $F,
+                $Join: FnMut(Vec<$F>) -> $F,
+                Sort: Problem<$F, N, G>,
+            )+
+        {
+            type Results = ($(HashMap<N::NodeId, NodeInfo<$F>>,)+);
+
+            const COUNT: usize = $count;
+
+            fn start(&mut self, graph: &G, stats: &mut Stats<N::NodeId>) -> VecDeque<N::NodeId> {
+                // Every analysis starts with the same worklist, so any of them
+                // can be used
+                let [worklist, ..] = [$(self.$index.start(graph, stats)),+];
+                worklist
+            }
+
+            fn update(
+                &mut self,
+                graph: &G,
+                index: usize,
+                id: N::NodeId,
+                stats: &mut Stats<N::NodeId>,
+            ) -> bool {
+                match index {
+                    $($index => self.$index.update(graph, id, &mut DefaultObserver, stats),)+
+                    _ => unreachable!(),
+                }
+            }
+
+            fn nexts<'g>(&self, graph: &'g G, id: N::NodeId) -> &'g [N::NodeId] {
+                self.0.nexts(graph, id)
+            }
+
+            // Events which aren't about a single analysis are reported as
+            // events of the first one
+            fn node_enqueued(&self, id: N::NodeId, len: usize) {
+                Observer::<N, F0>::node_enqueued(&mut DefaultObserver, id, len);
+            }
+
+            fn finished(&self, stats: &Stats<N::NodeId>) {
+                Observer::<N, F0>::finished(&mut DefaultObserver, stats);
+            }
+
+            fn finish(self) -> Self::Results {
+                ($(self.$index.take_infos(),)+)
+            }
+        }
+    };
+}
+
+analyses!(2; 0: F0 T0 J0, 1: F1 T1 J1);
+analyses!(3; 0: F0 T0 J0, 1: F1 T1 J1, 2: F2 T2 J2);
+analyses!(4; 0: F0 T0 J0, 1: F1 T1 J1, 2: F2 T2 J2, 3: F3 T3 J3);
+analyses!(5; 0: F0 T0 J0, 1: F1 T1 J1, 2: F2 T2 J2, 3: F3 T3 J3, 4: F4 T4 J4);
+analyses!(6; 0: F0 T0 J0, 1: F1 T1 J1, 2: F2 T2 J2, 3: F3 T3 J3, 4: F4 T4 J4, 5: F5 T5 J5);
+
+mod private {
+    /// Disallows downstream implementations of `Analyses`
+    pub trait Sealed {}
+}
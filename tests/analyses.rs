@@ -0,0 +1,188 @@
+//! The bundled analyses should give the same results as the hand-written ones
+//! in `live.rs` and `defassgn.rs`, on both the built-in statement graphs and
+//! other nodes implementing `DefUse`.
+
+use creek::analyses::{assign, live, DefUse};
+use creek::cfg::{BlockId, Variable};
+use creek::{text, Graph, HashSet, Node, NodeInfo};
+
+fn vars(ids: &[usize]) -> HashSet<Variable> {
+    ids.iter().map(|id| Variable(*id)).collect()
+}
+
+/// ```plain
+///       +-1-----+
+///       | a = 0 |
+///       | b = 1 |
+///       +-------+
+///        |     |
+///        v     v
+/// +-2-----+   +-3-----+
+/// | c = b |   | c = a |
+/// +-------+   +-------+
+///        |     |
+///        v     v
+///       +-4-----+
+///       | d = a |
+///       +-------+
+/// ```
+#[test]
+fn live_one_branch() {
+    let parsed = text::parse_statements(
+        "
+1 -> 2, 3 : a = 0; b = 1
+2 -> 4    : c = b
+3 -> 4    : c = a
+4         : d = a
+",
+    )
+    .unwrap();
+
+    let res = live::solve(&parsed.graph);
+    let info = |before: &[usize], after: &[usize]| NodeInfo {
+        before: live::LiveVars::new(vars(before)),
+        after: live::LiveVars::new(vars(after)),
+    };
+
+    assert_eq!(info(&[], &[0, 1]), res[&BlockId(1)]);
+    assert_eq!(info(&[0, 1], &[0]), res[&BlockId(2)]);
+    assert_eq!(info(&[0], &[0]), res[&BlockId(3)]);
+    assert_eq!(info(&[0], &[]), res[&BlockId(4)]);
+}
+
+/// ```plain
+/// +-1-----+
+/// | var a |
+/// +-------+
+///     |
+///     v
+/// +-2-----+
+/// | a = 0 |<-+
+/// +-------+  |
+///   |   |    |
+///   |   +----+
+///   v
+/// +-3-----+
+/// | b = a |
+/// +-------+
+/// ```
+#[test]
+fn assign_one_loop() {
+    let parsed = text::parse_statements(
+        "
+1 -> 2    : var a
+2 -> 2, 3 : a = 0
+3         : b = a
+",
+    )
+    .unwrap();
+
+    let res = assign::solve(&parsed.graph);
+    let info = |before: &[usize], after: &[usize]| NodeInfo {
+        before: assign::Unassigned::new(vars(before)),
+        after: assign::Unassigned::new(vars(after)),
+    };
+
+    assert_eq!(info(&[], &[0]), res[&BlockId(1)]);
+    assert_eq!(info(&[0], &[]), res[&BlockId(2)]);
+    assert_eq!(info(&[], &[]), res[&BlockId(3)]);
+}
+
+/// A single three-address instruction `dst = lhs + rhs`, in a straight line
+/// of instructions numbered from 0
+struct Add {
+    id: usize,
+    vars: [char; 3],
+    preds: Vec<usize>,
+    succs: Vec<usize>,
+}
+
+impl Node for Add {
+    type NodeId = usize;
+}
+
+impl DefUse for Add {
+    type Var = char;
+
+    fn stmt_count(&self) -> usize {
+        1
+    }
+
+    fn uses(&self, _stmt: usize) -> &[char] {
+        &self.vars[1..]
+    }
+
+    fn defs(&self, _stmt: usize) -> &[char] {
+        &self.vars[..1]
+    }
+}
+
+struct Straight {
+    nodes: Vec<Add>,
+    ids: Vec<usize>,
+}
+
+impl Straight {
+    fn new(instrs: &[[char; 3]]) -> Self {
+        let n = instrs.len();
+        let nodes = instrs
+            .iter()
+            .enumerate()
+            .map(|(id, vars)| Add {
+                id,
+                vars: *vars,
+                preds: if id == 0 { vec![] } else { vec![id - 1] },
+                succs: if id + 1 == n { vec![] } else { vec![id + 1] },
+            })
+            .collect();
+
+        Self {
+            nodes,
+            ids: (0..n).collect(),
+        }
+    }
+}
+
+impl Graph<Add> for Straight {
+    fn get(&self, id: usize) -> &Add {
+        &self.nodes[id]
+    }
+
+    fn get_entry(&self) -> usize {
+        0
+    }
+
+    fn get_exit(&self) -> usize {
+        self.nodes.len() - 1
+    }
+
+    fn get_preds(&self, id: usize) -> &[usize] {
+        &self.nodes[id].preds
+    }
+
+    fn get_succs(&self, id: usize) -> &[usize] {
+        &self.nodes[id].succs
+    }
+
+    fn get_all_node_ids(&self) -> &[usize] {
+        &self.ids
+    }
+}
+
+#[test]
+fn live_custom_node() {
+    // c = a + b; d = c + c; a = d + b
+    let graph = Straight::new(&[['c', 'a', 'b'], ['d', 'c', 'c'], ['a', 'd', 'b']]);
+    let res = live::solve(&graph);
+
+    let live = |id: usize| {
+        let mut live: Vec<_> = res[&id].before.live.iter().copied().collect();
+        live.sort();
+        live
+    };
+
+    assert_eq!(vec!['a', 'b'], live(0));
+    assert_eq!(vec!['b', 'c'], live(1));
+    assert_eq!(vec!['b', 'd'], live(2));
+    assert!(res[&graph.nodes[2].id].after.live.is_empty());
+}
@@ -0,0 +1,119 @@
+//! Bidirectional problems, where facts flow both with and against the edges
+
+use creek::cfg::{Block, BlockId, NodeGraph};
+use creek::lattice::{join_all, Flat, Lattice};
+use creek::{text, Bidirectional, HashMap, NodeInfo, Observer, Stats};
+
+type Types = HashMap<BlockId, NodeInfo<Flat<String>>>;
+
+/// A tiny type inference for a single variable, where `x: T` in any block
+/// means `x` has type `T` everywhere it can flow to or from
+fn infer(graph: &NodeGraph<String>) -> Types {
+    let constrain = |block: &Block<String>, ty: Flat<String>| match block.data.strip_prefix("x: ") {
+        Some(name) => ty.join(&Flat::Const(name.to_string())),
+        None => ty,
+    };
+
+    Bidirectional::new(Flat::Bottom, constrain, constrain, join_all).solve(graph)
+}
+
+/// ```plain
+/// +-1-----+    +-2-----+    +-3------+    +-4-----+
+/// | x = 1 |--->| y = x |--->| x: int |--->| z = x |
+/// +-------+    +-------+    +--------+    +-------+
+/// ```
+#[test]
+fn chain() {
+    let graph = text::parse("1 -> 2 : x = 1\n2 -> 3 : y = x\n3 -> 4 : x: int\n4 : z = x").unwrap();
+    let types = infer(&graph);
+    let int = Flat::Const("int".to_string());
+
+    for id in 1..=4 {
+        assert_eq!(types[&BlockId(id)].before, int);
+        assert_eq!(types[&BlockId(id)].after, int);
+    }
+}
+
+/// Constraints from a branch reach the other branch through the blocks they
+/// share, and conflicting constraints are found
+///
+/// ```plain
+///         +-1-----+
+///         | x = 1 |
+///         +-------+
+///          |     |
+///          v     v
+/// +-2------+    +-3-------+
+/// | x: int |    | y = x   |
+/// +--------+    +---------+
+///          |     |
+///          v     v
+///         +-4-----+
+///         | z = x |
+///         +-------+
+/// ```
+#[test]
+fn diamond() {
+    let src = "1 -> 2, 3 : x = 1\n2 -> 4 : x: int\n3 -> 4 : y = x\n4 : z = x";
+    let graph = text::parse(src).unwrap();
+    let types = infer(&graph);
+    assert_eq!(types[&BlockId(3)].after, Flat::Const("int".to_string()));
+
+    let graph = text::parse(&src.replace("y = x", "x: str")).unwrap();
+    let types = infer(&graph);
+    assert!(types.values().all(|info| info.before == Flat::Top));
+
+    // The entry fact flows backwards from the entry as well as forwards
+    let graph = text::parse(&src.replace("x: int", "w = x")).unwrap();
+    let types = Bidirectional::new(
+        Flat::Bottom,
+        |_: &Block<String>, t| t,
+        |_: &Block<String>, t| t,
+        join_all,
+    )
+    .with_entry_fact(Flat::Const("bool".to_string()))
+    .solve(&graph);
+    assert!(types
+        .values()
+        .all(|info| info.after == Flat::Const("bool".to_string())));
+}
+
+#[derive(Default)]
+struct Counts {
+    changed: usize,
+    stats: Option<Stats<BlockId>>,
+}
+
+impl Observer<Block<String>, Flat<String>> for Counts {
+    fn fact_changed(&mut self, _: BlockId, _: &Flat<String>, _: &Flat<String>) {
+        self.changed += 1;
+    }
+
+    fn finished(&mut self, stats: &Stats<BlockId>) {
+        self.stats = Some(stats.clone());
+    }
+}
+
+/// Observers see the nodes of the graph being solved, with both sides of
+/// every node counted as visits to it
+#[test]
+fn observed() {
+    let graph = text::parse("1 -> 2 : x = 1\n2 -> 3 : x: int\n3 : y = x").unwrap();
+    let constrain = |block: &Block<String>, ty: Flat<String>| match block.data.strip_prefix("x: ") {
+        Some(name) => ty.join(&Flat::Const(name.to_string())),
+        None => ty,
+    };
+
+    let mut counts = Counts::default();
+    let types = Bidirectional::new(Flat::Bottom, constrain, constrain, join_all)
+        .solve_observed(&graph, &mut counts);
+    assert_eq!(types, infer(&graph));
+
+    // Every side of every node goes from bottom to `int` once
+    assert_eq!(counts.changed, 6);
+
+    let stats = counts.stats.unwrap();
+    assert_eq!(stats.visits.len(), 3);
+    assert!(stats.visits.values().all(|visits| *visits >= 2));
+    assert!(stats.trans_calls >= 6);
+}
@@ -0,0 +1,67 @@
+//! The entry (or exit) fact is joined into the starting node, whether or not
+//! it has predecessors (or successors) of its own.
+
+use creek::cfg::{Block, BlockId, NodeGraph};
+use creek::{Analyzer, Fact};
+use fnv::FnvHashSet;
+
+/// The ids of the blocks seen along some path, and any marks from the boundary
+#[derive(Clone, Debug, PartialEq)]
+struct Seen(FnvHashSet<usize>);
+
+impl Fact for Seen {}
+
+fn trans(block: &Block<()>, mut fact: Seen) -> Seen {
+    fact.0.insert(block.id.0);
+    fact
+}
+
+fn join(facts: Vec<Seen>) -> Seen {
+    Seen(facts.into_iter().flat_map(|fact| fact.0).collect())
+}
+
+fn seen(ids: &[usize]) -> Seen {
+    Seen(ids.iter().copied().collect())
+}
+
+/// ```plain
+/// 1 -> 2 -> 3
+///      ^    |
+///      +----+
+/// ```
+fn line() -> NodeGraph<()> {
+    let mut graph = NodeGraph::new(Block::new(BlockId(1), ()));
+    graph.insert(Block::new(BlockId(2), ()));
+    graph.insert_exit(Block::new(BlockId(3), ()));
+    graph.add_edge(BlockId(1), BlockId(2));
+    graph.add_edge(BlockId(2), BlockId(3));
+    graph.add_edge(BlockId(3), BlockId(2));
+    graph
+}
+
+#[test]
+fn entry_fact() {
+    let graph = line();
+
+    let mut analyzer = Analyzer::new_forward(seen(&[]), trans, join).with_entry_fact(seen(&[0]));
+    let res = analyzer.solve(&graph);
+    assert_eq!(res[&BlockId(1)].before, seen(&[0]));
+    assert_eq!(res[&BlockId(3)].after, seen(&[0, 1, 2, 3]));
+
+    // A loop back to the entry doesn't replace the entry fact
+    let mut graph = line();
+    graph.add_edge(BlockId(3), BlockId(1));
+
+    let res = analyzer.solve(&graph);
+    assert_eq!(res[&BlockId(1)].before, seen(&[0, 1, 2, 3]));
+}
+
+#[test]
+fn exit_fact() {
+    let graph = line();
+
+    let mut analyzer = Analyzer::new_backward(seen(&[]), trans, join).with_exit_fact(seen(&[4]));
+    let res = analyzer.solve(&graph);
+    assert_eq!(res[&BlockId(3)].after, seen(&[2, 3, 4]));
+    assert_eq!(res[&BlockId(1)].before, seen(&[1, 2, 3, 4]));
+}
@@ -0,0 +1,76 @@
+//! Context sensitivity with k-limited call strings
+
+mod program;
+
+use creek::callstrings::{CallStrings, Context};
+use creek::cfg::{Block, BlockId};
+use creek::lattice::{join_all, Flat};
+use program::{words, Funcs};
+
+/// Tracks a single value, which `set 5` sets and everything else keeps
+fn trans(node: &Block<String>, fact: Flat<i64>) -> Flat<i64> {
+    match words(node).as_slice() {
+        ["set", c] => Flat::Const(c.parse().unwrap()),
+        _ => fact,
+    }
+}
+
+fn program() -> Funcs {
+    Funcs::parse(&[
+        // 0: main, which calls 2 through 1 with two different values
+        "1 -> 2 : set 1\n2 -> 3 : call 1\n3 -> 4 : set 2\n4 : call 1",
+        // 1: passes its value on to 2
+        "1 : call 2",
+        // 2: the identity function
+        "1 : nop",
+    ])
+}
+
+#[test]
+fn precision_grows_with_k() {
+    let program = program();
+    let top = Context::empty();
+
+    let res = CallStrings::new(0, Flat::Bottom, trans, join_all).solve(&program, 0);
+    assert_eq!(res[&0][&(BlockId(2), top.clone())].after, Flat::Top);
+    assert_eq!(res[&2].len(), 1);
+
+    // One call site isn't enough to tell the calls to 2 apart, since they
+    // both come from the same node in 1
+    let res = CallStrings::new(1, Flat::Bottom, trans, join_all).solve(&program, 0);
+    assert_eq!(res[&0][&(BlockId(2), top.clone())].after, Flat::Top);
+    assert_eq!(res[&1].len(), 2);
+
+    let res = CallStrings::new(2, Flat::Bottom, trans, join_all).solve(&program, 0);
+    assert_eq!(res[&0][&(BlockId(2), top.clone())].after, Flat::Const(1));
+    assert_eq!(res[&0][&(BlockId(4), top)].after, Flat::Const(2));
+
+    let ctx = Context::empty()
+        .push(0, BlockId(4), 2)
+        .push(1, BlockId(1), 2);
+    assert_eq!(ctx.sites(), &[(0, BlockId(4)), (1, BlockId(1))]);
+    assert_eq!(res[&2][&(BlockId(1), ctx)].after, Flat::Const(2));
+}
+
+#[test]
+fn recursion() {
+    let program = Funcs::parse(&[
+        // 0: main
+        "1 -> 2 : set 3\n2 : call 1",
+        // 1: a recursive function which returns its value
+        "1 -> 2, 3 : nop\n2 -> 3 : call 1\n3",
+    ]);
+
+    let res = CallStrings::new(2, Flat::Bottom, trans, join_all)
+        .with_entry_fact(Flat::Const(0))
+        .solve(&program, 0);
+
+    assert_eq!(
+        res[&0][&(BlockId(2), Context::empty())].after,
+        Flat::Const(3)
+    );
+
+    // Called from main, then from itself once or more
+    let contexts = res[&1].keys().filter(|(id, _)| *id == BlockId(1)).count();
+    assert_eq!(contexts, 3);
+}
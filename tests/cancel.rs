@@ -0,0 +1,54 @@
+//! A solve can be cancelled between visits, giving back what it has so far
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use creek::analyses::constants;
+use creek::{text, Analyzer};
+
+const LOOP: &str = "
+1 -> 2       : a = 1; b = 2; c = a + b
+2 -> 3, 4    : d = a + b; b = c
+3 -> 2       : a = a * b; e = 7
+4            : f = a + b; g = e
+";
+
+#[test]
+fn cancelled_partway() {
+    let graph = text::parse_statements(LOOP).unwrap();
+    let graph = &graph.graph;
+    let mut analyzer = Analyzer::new_forward_lattice(constants::trans);
+
+    // Let two nodes be visited, then give up
+    let mut checks = 0;
+    let cancelled = analyzer
+        .solve_cancellable(graph, || {
+            checks += 1;
+            checks > 2
+        })
+        .unwrap_err();
+
+    assert_eq!(cancelled.worklist.len(), 2);
+    assert!(cancelled.infos.len() >= 2);
+    assert_eq!(
+        cancelled.to_string(),
+        "solve cancelled with 2 nodes left to visit"
+    );
+
+    // The analyzer still works afterwards
+    let res = analyzer.solve_cancellable(graph, || false);
+    assert_eq!(res, Ok(constants::solve(graph)));
+}
+
+#[test]
+fn cancelled_by_flag() {
+    let graph = text::parse_statements(LOOP).unwrap();
+    let graph = &graph.graph;
+    let mut analyzer = Analyzer::new_forward_lattice(constants::trans);
+
+    let flag = AtomicBool::new(true);
+    let res = analyzer.solve_cancellable(graph, || flag.load(Ordering::Relaxed));
+    let cancelled = res.unwrap_err();
+
+    assert_eq!(cancelled.worklist.len(), 4);
+    assert_eq!(cancelled.infos.len(), 1);
+}
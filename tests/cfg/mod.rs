@@ -1,27 +1,14 @@
 //! A simple control flow graph. A `NodeGraph` consists of one or more blocks,
-//! each of which consists of multiple statements. Each statement is either a
-//! constant assignment, like `x = 5`, or a variable assignment, like `x = a`.
+//! each of which consists of multiple statements from `creek::cfg`.
 
 #![allow(dead_code)]
 
 pub mod macros;
 
+pub use creek::cfg::{Statement, Variable};
 use creek::{Graph, Node};
 use fnv::FnvHashMap;
 
-/// A variable with a unique id
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-pub struct Variable(pub usize);
-
-/// A simple statement, which is either a constant assignment (`x = 5`) or a
-/// variable assignment (`x = a`)
-#[derive(Debug)]
-pub enum Statement {
-    Declare(Variable),
-    ConstAssign(Variable, i32),
-    VarAssign(Variable, Variable),
-}
-
 /// The unique id for a block
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct BlockId(pub usize);
@@ -65,9 +52,8 @@ impl NodeGraph {
     /// Insert a block
     pub fn insert(&mut self, block: Block) {
         self.block_ids.push(block.id);
-        match self.blocks.insert(block.id, block) {
-            Some(block) => panic!("{:?}", block.id),
-            None => {}
+        if let Some(block) = self.blocks.insert(block.id, block) {
+            panic!("{:?}", block.id);
         }
     }
 
@@ -75,9 +61,8 @@ impl NodeGraph {
     pub fn insert_entry(&mut self, block: Block) {
         self.block_ids.push(block.id);
         self.entry = block.id;
-        match self.blocks.insert(block.id, block) {
-            Some(block) => panic!("{:?}", block.id),
-            None => {}
+        if let Some(block) = self.blocks.insert(block.id, block) {
+            panic!("{:?}", block.id);
         }
     }
 
@@ -85,9 +70,8 @@ impl NodeGraph {
     pub fn insert_exit(&mut self, block: Block) {
         self.block_ids.push(block.id);
         self.exit = block.id;
-        match self.blocks.insert(block.id, block) {
-            Some(block) => panic!("{:?}", block.id),
-            None => {}
+        if let Some(block) = self.blocks.insert(block.id, block) {
+            panic!("{:?}", block.id);
         }
     }
 }
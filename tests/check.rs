@@ -0,0 +1,92 @@
+//! Checking the lattice laws and monotonicity finds broken `join` and `trans`
+//! functions
+
+use creek::cfg::{Block, BlockId};
+use creek::check::{self, Violation};
+use creek::lattice::{join_all, Flat, PowerSet};
+use creek::text;
+
+fn facts() -> Vec<Flat<i64>> {
+    vec![Flat::Bottom, Flat::Const(1), Flat::Const(2), Flat::Top]
+}
+
+/// Forgets everything it knows, which makes more precise facts give less
+/// precise results
+fn reset(_: &Block<String>, fact: Flat<i64>) -> Flat<i64> {
+    match fact {
+        Flat::Top => Flat::Const(0),
+        fact => fact,
+    }
+}
+
+#[test]
+fn join_laws() {
+    let sets: Vec<PowerSet<u8>> = vec![
+        PowerSet::empty(),
+        vec![1].into_iter().collect(),
+        vec![1, 2].into_iter().collect(),
+        PowerSet::all(),
+    ];
+    assert_eq!(
+        check::check_join(&sets, &PowerSet::empty(), join_all),
+        Ok(())
+    );
+    assert_eq!(check::check_join(&facts(), &Flat::Bottom, join_all), Ok(()));
+
+    // `Top` is the identity of meet, not join
+    assert_eq!(
+        check::check_join(&facts(), &Flat::Top, join_all),
+        Err(Violation::NotIdentity(Flat::Bottom))
+    );
+
+    // Keeping the first fact isn't commutative
+    let first = |facts: Vec<Flat<i64>>| facts[0];
+    assert_eq!(
+        check::check_join(&facts(), &Flat::Bottom, first),
+        Err(Violation::NotCommutative(Flat::Bottom, Flat::Const(1)))
+    );
+}
+
+#[test]
+fn monotone() {
+    let graph = text::parse("1 -> 2 : a\n2 : b").unwrap();
+    let keep = |_: &Block<String>, fact: Flat<i64>| fact;
+
+    assert_eq!(check::check_trans(&graph, &facts(), keep, join_all), Ok(()));
+    assert_eq!(
+        check::check_trans(&graph, &facts(), reset, join_all),
+        Err(Violation::NotMonotone {
+            id: BlockId(1),
+            smaller: Flat::Const(1),
+            larger: Flat::Top,
+        })
+    );
+}
+
+#[cfg(feature = "proptest")]
+#[test]
+fn shrinking() {
+    use creek::lattice::Lattice;
+    use proptest::prelude::*;
+    use proptest::test_runner::TestError;
+
+    let flat = prop_oneof![
+        Just(Flat::Bottom),
+        (0..100i64).prop_map(Flat::Const),
+        Just(Flat::Top),
+    ];
+
+    assert!(check::prop_join(flat.clone(), &Flat::bottom(), join_all).is_ok());
+
+    let graph = text::parse("1 : a").unwrap();
+    match check::prop_trans(&graph, flat, reset, join_all) {
+        // The counterexample is a constant, and something which joins with
+        // it to `Top`
+        Err(TestError::Fail(_, (a, b))) => {
+            assert_ne!(a, Flat::Const(0));
+            assert!(a.as_const().is_some());
+            assert_eq!(a.join(&b), Flat::Top);
+        }
+        res => panic!("expected a counterexample, got {:?}", res),
+    }
+}
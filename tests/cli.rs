@@ -0,0 +1,73 @@
+//! The command-line tool should print the facts for every block in the
+//! requested format.
+
+#![cfg(feature = "cli")]
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const ONE_BRANCH: &str = "
+1 entry -> 2, 3 : a = 0; b = 1
+2 -> 4          : c = b
+3 -> 4          : c = a
+4 exit          : d = a
+";
+
+fn run(args: &[&str], input: &str) -> (bool, String, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_creek"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+
+    let out = child.wait_with_output().unwrap();
+    (
+        out.status.success(),
+        String::from_utf8(out.stdout).unwrap(),
+        String::from_utf8(out.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn live_table_and_json() {
+    let (ok, out, _) = run(&["live"], ONE_BRANCH);
+    assert!(ok);
+    assert_eq!(
+        "\
+block  before  after
+1      {}      {a, b}
+2      {a, b}  {a}
+3      {a}     {a}
+4      {a}     {}
+",
+        out
+    );
+
+    let (ok, out, _) = run(&["live", "--format", "json"], ONE_BRANCH);
+    assert!(ok);
+    assert!(out.contains(r#"{"id": 2, "before": ["a", "b"], "after": ["a"]}"#));
+
+    let (ok, out, _) = run(&["live", "--format", "dot"], ONE_BRANCH);
+    assert!(ok);
+    assert!(out.starts_with("digraph {"));
+}
+
+#[test]
+fn reports_errors() {
+    let (ok, _, err) = run(&["live"], "1 -> 2\n");
+    assert!(!ok);
+    assert_eq!("error: <stdin>:1:6: block 2 is never defined\n", err);
+
+    let (ok, _, err) = run(&["nonsense"], ONE_BRANCH);
+    assert!(!ok);
+    assert!(err.starts_with("error: unknown analysis `nonsense`"));
+}
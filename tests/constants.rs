@@ -0,0 +1,95 @@
+//! Constant propagation over the flat lattice
+
+use creek::analyses::constants::{self, Env};
+use creek::cfg::{BlockId, Variable};
+use creek::lattice::{join_all, Flat, Lattice, MapLattice};
+use creek::text;
+
+#[test]
+fn flat_join() {
+    let one = Flat::Const(1);
+    let two = Flat::Const(2);
+
+    assert_eq!(join_all::<Flat<i32>>(vec![]), Flat::Bottom);
+    assert_eq!(Flat::Bottom.join(&one), one);
+    assert_eq!(one.join(&one), one);
+    assert_eq!(one.join(&two), Flat::Top);
+    assert_eq!(Flat::<i32>::Top.join(&Flat::Bottom), Flat::Top);
+
+    let lhs: MapLattice<_, _> = vec![(1, one), (2, one)].into_iter().collect();
+    let rhs: MapLattice<_, _> = vec![(1, one), (3, two)].into_iter().collect();
+    let joined = lhs.join(&rhs);
+    assert_eq!(joined.get(&1), one);
+    assert_eq!(joined.get(&2), one);
+    assert_eq!(joined.get(&3), two);
+    assert_eq!(joined.get(&4), Flat::Bottom);
+
+    // Bottom values aren't stored, so they don't affect equality
+    let mut with_bottom = joined.clone();
+    with_bottom.insert(4, Flat::Bottom);
+    assert_eq!(with_bottom, joined);
+}
+
+/// ```plain
+///       +-1-----+
+///       | a = 2 |
+///       | b = 3 |
+///       +-------+
+///        |     |
+///        v     v
+/// +-2-------+ +-3-------+
+/// | c = a*b | | c = 6   |
+/// | d = 1   | | d = 2   |
+/// +---------+ +---------+
+///        |     |
+///        v     v
+///     +-4-----------+
+///     | e = c + a   |
+///     | f = d       |
+///     +-------------+
+/// ```
+#[test]
+fn diamond() {
+    let graph = text::parse_statements(
+        "
+        1 -> 2, 3 : a = 2; b = 3
+        2 -> 4    : c = a * b; d = 1
+        3 -> 4    : c = 6; d = 2
+        4         : e = c + a; f = d
+        ",
+    )
+    .unwrap();
+
+    let res = constants::solve(&graph.graph);
+    let var = |name: &str| Variable(graph.names.iter().position(|n| n == name).unwrap());
+    let after = |id: usize| -> &Env { &res[&BlockId(id)].after };
+
+    assert_eq!(after(2).get(&var("c")), Flat::Const(6));
+    assert_eq!(res[&BlockId(4)].before.get(&var("c")), Flat::Const(6));
+    assert_eq!(res[&BlockId(4)].before.get(&var("d")), Flat::Top);
+    assert_eq!(after(4).get(&var("e")), Flat::Const(8));
+    assert_eq!(after(4).get(&var("f")), Flat::Top);
+}
+
+/// A variable which changes around a loop isn't constant, but one which is
+/// only copied is
+#[test]
+fn loop_counter() {
+    let graph = text::parse_statements(
+        "
+        1 entry -> 2 : i = 0; one = 1; k = 5
+        2 -> 2, 3    : i = i + one; j = k
+        3 exit       : var x; y = x + one
+        ",
+    )
+    .unwrap();
+
+    let res = constants::solve(&graph.graph);
+    let var = |name: &str| Variable(graph.names.iter().position(|n| n == name).unwrap());
+    let exit = &res[&BlockId(3)].after;
+
+    assert_eq!(exit.get(&var("i")), Flat::Top);
+    assert_eq!(exit.get(&var("j")), Flat::Const(5));
+    assert_eq!(exit.get(&var("one")), Flat::Const(1));
+    assert_eq!(exit.get(&var("y")), Flat::Top);
+}
@@ -39,13 +39,16 @@ fn trans(block: &Block, mut fact: AssignmentFact) -> AssignmentFact {
     for stmt in block.stmts.iter() {
         match stmt {
             Statement::Declare(var) => {
-                fact.uninit.insert(var.clone());
+                fact.uninit.insert(*var);
             }
             Statement::ConstAssign(var, _) => {
-                fact.uninit.remove(&var);
+                fact.uninit.remove(var);
             }
             Statement::VarAssign(var, _) => {
-                fact.uninit.remove(&var);
+                fact.uninit.remove(var);
+            }
+            Statement::ExprAssign(var, _) => {
+                fact.uninit.remove(var);
             }
         }
     }
@@ -0,0 +1,91 @@
+//! DOT output should contain every node and edge, with facts and highlights
+//! where asked for.
+
+mod cfg;
+
+use cfg::*;
+use creek::{dot, Fact, NodeInfo};
+
+#[derive(Clone, Debug, PartialEq)]
+struct CountFact(usize);
+
+impl Fact for CountFact {}
+
+/// ```plain
+/// 1 -> 2 -> 3
+///      ^    |
+///      +----+
+/// ```
+fn graph() -> NodeGraph {
+    let mut graph = NodeGraph::new(block! {
+        1;
+        from => ;
+        to => 2;
+        (0 = 0)
+    });
+
+    graph.insert(block! {
+        2;
+        from => 1, 3;
+        to => 3;
+        (1 = var 0)
+    });
+
+    graph.insert_exit(block! {
+        3;
+        from => 2;
+        to => 2;
+        (2 = var 1)
+    });
+
+    graph
+}
+
+fn label(block: &Block) -> String {
+    format!("block \"{}\"", block.id.0)
+}
+
+#[test]
+fn nodes_and_edges() {
+    let out = dot::render(&graph(), label);
+
+    assert!(out.starts_with("digraph {"));
+    assert!(out.contains("n0 [label=\"block \\\"1\\\"\\l\", peripheries=2];"));
+    assert!(out.contains("n1 [label=\"block \\\"2\\\"\\l\"];"));
+    assert!(out.contains("n0 -> n1;"));
+    assert!(out.contains("n1 -> n2;"));
+    assert!(out.contains("n2 -> n1;"));
+    assert_eq!(3, out.matches("->").count());
+}
+
+#[test]
+fn facts_and_changes() {
+    let info = |before, after| NodeInfo {
+        before: CountFact(before),
+        after: CountFact(after),
+    };
+
+    let old = dict![
+        BlockId(1) => info(0, 1),
+        BlockId(2) => info(1, 2),
+        BlockId(3) => info(2, 3)
+    ];
+
+    let new = dict![
+        BlockId(1) => info(0, 1),
+        BlockId(2) => info(1, 5),
+        BlockId(3) => info(5, 6)
+    ];
+
+    let fmt = |f: &CountFact| f.0.to_string();
+
+    let out = dot::render_facts(&graph(), label, &new, fmt);
+    assert!(out.contains("before: 1\\lafter: 5\\l"));
+    assert!(!out.contains("fillcolor"));
+
+    let out = dot::render_changes(&graph(), label, &old, &new, fmt);
+    assert_eq!(2, out.matches("fillcolor").count());
+    assert!(!out
+        .lines()
+        .any(|l| l.contains("n0 [") && l.contains("fillcolor")));
+}
@@ -0,0 +1,103 @@
+//! Available and very busy expressions are must problems, joined by
+//! intersection.
+
+use creek::analyses::{available, busy};
+use creek::cfg::{BinOp, BlockId, Expr, Variable};
+use creek::lattice::{Must, PowerSet};
+use creek::text;
+
+/// ```plain
+///       +-1---------+
+///       | c = a + b |
+///       | d = a * b |
+///       +-----------+
+///        |         |
+///        v         v
+/// +-2---------+   +-3---------+
+/// | e = a + b |   | f = a + b |
+/// | a = c     |   +-----------+
+/// +-----------+    |
+///        |         |
+///        v         v
+///       +-4---------+
+///       | g = a + b |
+///       | h = a * b |
+///       +-----------+
+/// ```
+const DIAMOND: &str = "
+1 -> 2, 3 : c = a + b; d = a * b
+2 -> 4    : e = a + b; a = c
+3 -> 4    : f = a + b
+4         : g = a + b; h = a * b
+";
+
+fn set(exprs: &[Expr]) -> PowerSet<Expr, Must> {
+    exprs.iter().copied().collect()
+}
+
+fn exprs() -> (Expr, Expr) {
+    let (a, b) = (Variable(1), Variable(2));
+    let add = Expr {
+        op: BinOp::Add,
+        args: [a, b],
+    };
+    let mul = Expr {
+        op: BinOp::Mul,
+        args: [a, b],
+    };
+
+    (add, mul)
+}
+
+#[test]
+fn available_diamond() {
+    let parsed = text::parse_statements(DIAMOND).unwrap();
+    let res = available::solve(&parsed.graph);
+    let (add, mul) = exprs();
+
+    assert_eq!(set(&[]), res[&BlockId(1)].before);
+    assert_eq!(set(&[add, mul]), res[&BlockId(1)].after);
+    assert_eq!(set(&[]), res[&BlockId(2)].after);
+    assert_eq!(set(&[add, mul]), res[&BlockId(3)].after);
+
+    // Assigning `a` in block 2 kills both expressions on that path
+    assert_eq!(set(&[]), res[&BlockId(4)].before);
+    assert_eq!(set(&[add, mul]), res[&BlockId(4)].after);
+}
+
+#[test]
+fn busy_diamond() {
+    let parsed = text::parse_statements(DIAMOND).unwrap();
+    let res = busy::solve(&parsed.graph);
+    let (add, mul) = exprs();
+
+    assert_eq!(set(&[add, mul]), res[&BlockId(1)].before);
+    assert_eq!(set(&[add]), res[&BlockId(1)].after);
+    assert_eq!(set(&[add]), res[&BlockId(2)].before);
+    assert_eq!(set(&[add, mul]), res[&BlockId(3)].before);
+    assert_eq!(set(&[]), res[&BlockId(4)].after);
+}
+
+/// The entry fact must still be joined in when the entry block is the target
+/// of a back edge
+#[test]
+fn available_entry_loop() {
+    let parsed = text::parse_statements(
+        "
+1 -> 1, 2 : c = a + b
+2         : d = c
+",
+    )
+    .unwrap();
+
+    let res = available::solve(&parsed.graph);
+    let (a, b) = (Variable(1), Variable(2));
+    let add = Expr {
+        op: BinOp::Add,
+        args: [a, b],
+    };
+
+    assert_eq!(set(&[]), res[&BlockId(1)].before);
+    assert_eq!(set(&[add]), res[&BlockId(1)].after);
+    assert_eq!(set(&[add]), res[&BlockId(2)].before);
+}
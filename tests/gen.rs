@@ -0,0 +1,200 @@
+//! Random graphs have the requested shape, and every way of solving them gives
+//! the same facts
+
+#![cfg(feature = "gen")]
+
+use creek::analyses::{constants, live, reaching};
+use creek::cfg::{BlockId, NodeGraph};
+use creek::gen::{self, Config, Rng};
+use creek::{solve_together, Analyzer, Graph, HashSet};
+
+fn reachable<T>(graph: &NodeGraph<T>) -> HashSet<BlockId> {
+    let mut seen = HashSet::default();
+    let mut stack = vec![graph.get_entry()];
+
+    while let Some(id) = stack.pop() {
+        if seen.insert(id) {
+            stack.extend(graph.get_succs(id));
+        }
+    }
+
+    seen
+}
+
+#[test]
+fn shape() {
+    let config = Config {
+        blocks: 20,
+        loops: 0.5,
+        irreducible: 0.2,
+        unreachable: 3,
+        exits: 3,
+        ..Config::default()
+    };
+
+    for seed in 0..50 {
+        let graph = gen::graph(&config, &mut Rng::new(seed), |_, _| ());
+
+        assert_eq!(graph.len(), 23);
+        assert_eq!(reachable(&graph).len(), 20);
+        assert_eq!(graph.get_exit(), BlockId(20));
+
+        let ends = graph.blocks().filter(|b| b.succs.is_empty()).count();
+        assert_eq!(ends, 3);
+
+        // The same seed always gives the same graph
+        let again = gen::graph(&config, &mut Rng::new(seed), |_, _| ());
+        assert!(graph.blocks().eq(again.blocks()));
+    }
+}
+
+/// Find the blocks which dominate each block, including itself
+fn dominators<T>(graph: &NodeGraph<T>) -> Vec<HashSet<usize>> {
+    let n = graph.len();
+    let all: HashSet<_> = (1..=n).collect();
+
+    // dom(i) = {i} + the blocks in dom(p) for every predecessor p
+    let mut doms = vec![all; n + 1];
+    doms[1] = std::iter::once(1).collect();
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for i in 2..=n {
+            let mut dom = graph
+                .get_preds(BlockId(i))
+                .iter()
+                .map(|p| doms[p.0].clone())
+                .fold(None, |acc: Option<HashSet<_>>, d| match acc {
+                    None => Some(d),
+                    Some(acc) => Some(acc.intersection(&d).copied().collect()),
+                })
+                .unwrap_or_default();
+            dom.insert(i);
+
+            if dom != doms[i] {
+                doms[i] = dom;
+                changed = true;
+            }
+        }
+    }
+
+    doms
+}
+
+/// Get the edges back to an earlier block (or to the same one) which doesn't
+/// dominate their source
+fn irreducible<T>(graph: &NodeGraph<T>) -> Vec<(BlockId, BlockId)> {
+    let doms = dominators(graph);
+
+    graph
+        .blocks()
+        .flat_map(|b| b.succs.iter().map(move |s| (b.id, *s)))
+        .filter(|(from, to)| to.0 <= from.0 && !doms[from.0].contains(&to.0))
+        .collect()
+}
+
+/// Check whether there is a path from `from` to `to`
+fn reaches<T>(graph: &NodeGraph<T>, from: BlockId, to: BlockId) -> bool {
+    let mut seen = HashSet::default();
+    let mut stack = vec![from];
+
+    while let Some(id) = stack.pop() {
+        if id == to {
+            return true;
+        }
+
+        if seen.insert(id) {
+            stack.extend(graph.get_succs(id));
+        }
+    }
+
+    false
+}
+
+#[test]
+fn loops() {
+    let config = Config {
+        blocks: 20,
+        loops: 0.5,
+        ..Config::default()
+    };
+
+    // Every loop has a single entry
+    for seed in 0..50 {
+        let graph = gen::graph(&config, &mut Rng::new(seed), |_, _| ());
+        assert_eq!(irreducible(&graph), Vec::new());
+    }
+
+    let config = Config {
+        loops: 0.0,
+        irreducible: 0.5,
+        ..config
+    };
+
+    // Every edge to a block which isn't a dominator closes a loop
+    let mut edges = 0;
+    for seed in 0..50 {
+        let graph = gen::graph(&config, &mut Rng::new(seed), |_, _| ());
+
+        for (from, to) in irreducible(&graph) {
+            assert!(reaches(&graph, to, from), "{:?} -> {:?}", from, to);
+            edges += 1;
+        }
+    }
+
+    assert!(edges > 0);
+}
+
+#[test]
+fn solvers_agree() {
+    let config = Config {
+        blocks: 15,
+        loops: 0.4,
+        irreducible: 0.2,
+        unreachable: 2,
+        exits: 2,
+        ..Config::default()
+    };
+
+    for seed in 0..50 {
+        let mut rng = Rng::new(seed);
+        let graph = gen::graph(&config, &mut rng, |rng, _| gen::statements(rng, 4, 3));
+
+        let consts = constants::solve(&graph);
+        let defs = reaching::solve(&graph);
+        let vars = live::solve(&graph);
+
+        let mut analyzer = Analyzer::new_forward_lattice(constants::trans);
+        assert_eq!(analyzer.solve_steps(&graph).finish(), consts);
+
+        #[cfg(feature = "rayon")]
+        assert_eq!(analyzer.solve_parallel(&graph), consts);
+
+        let mut query = analyzer.query(&graph);
+        for id in graph.get_all_node_ids() {
+            assert_eq!(query.get(*id), &consts[id]);
+        }
+
+        let mut reaching = Analyzer::new_forward(
+            reaching::ReachingDefs::new(HashSet::default()),
+            reaching::trans,
+            reaching::join,
+        );
+        let mut constants = Analyzer::new_forward_lattice(constants::trans);
+        assert_eq!(
+            solve_together(&graph, (&mut reaching, &mut constants)),
+            (defs, consts)
+        );
+
+        let mut live = Analyzer::new_backward(
+            live::LiveVars::new(HashSet::default()),
+            live::trans,
+            live::join,
+        );
+        let mut query = live.query(&graph);
+        for id in graph.get_all_node_ids() {
+            assert_eq!(query.get(*id), &vars[id]);
+        }
+    }
+}
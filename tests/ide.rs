@@ -0,0 +1,167 @@
+//! Linear constant propagation with the IDE solver, which is context sensitive
+//! across calls
+
+mod program;
+
+use creek::cfg::{Block, BlockId};
+use creek::ide::{self, EdgeFn, Edges, Linear};
+use creek::ifds::Flows;
+use creek::lattice::Flat;
+use program::{words, Funcs};
+
+/// Linear constant propagation, where facts are variables and the zero fact is
+/// the empty string. Payloads are `x = 5`, `x = input`, `x = y`, `x = y + 1`,
+/// `x = y * 2 + 1` or `call f a b`, which passes `a` to `f` as `p` and puts its
+/// result `r` into `b`.
+struct Constants;
+
+enum Src<'a> {
+    Const(i64),
+    Input,
+    Var(&'a str, i64, i64),
+}
+
+fn zero() -> String {
+    String::new()
+}
+
+fn assign(node: &Block<String>) -> Option<(&str, Src<'_>)> {
+    let src = match words(node).as_slice() {
+        [_, "=", "input"] => Src::Input,
+        [_, "=", y] => match y.parse() {
+            Ok(c) => Src::Const(c),
+            Err(_) => Src::Var(y, 1, 0),
+        },
+        [_, "=", y, "+", b] => Src::Var(y, 1, b.parse().unwrap()),
+        [_, "=", y, "*", a, "+", b] => Src::Var(y, a.parse().unwrap(), b.parse().unwrap()),
+        _ => return None,
+    };
+
+    Some((words(node)[0], src))
+}
+
+impl Flows<Block<String>, Funcs> for Constants {
+    type Fact = String;
+
+    fn zero(&self) -> String {
+        zero()
+    }
+
+    fn normal(&self, _: usize, node: &Block<String>, fact: &String) -> Vec<String> {
+        let (x, src) = match assign(node) {
+            Some(assign) => assign,
+            None => return vec![fact.clone()],
+        };
+
+        let mut facts = Vec::new();
+        if fact.is_empty() {
+            facts.push(zero());
+        } else if fact != x {
+            facts.push(fact.clone());
+        }
+
+        match src {
+            Src::Const(_) | Src::Input if fact.is_empty() => facts.push(x.to_string()),
+            Src::Var(y, _, _) if fact == y => facts.push(x.to_string()),
+            _ => {}
+        }
+
+        facts
+    }
+
+    fn call(&self, _: usize, node: &Block<String>, _: usize, fact: &String) -> Vec<String> {
+        match words(node).as_slice() {
+            _ if fact.is_empty() => vec![zero()],
+            ["call", _, a, _] if fact == a => vec!["p".to_string()],
+            _ => Vec::new(),
+        }
+    }
+
+    fn ret(&self, _: usize, node: &Block<String>, _: usize, fact: &String) -> Vec<String> {
+        match words(node).as_slice() {
+            _ if fact.is_empty() => vec![zero()],
+            ["call", _, _, b] if fact == "r" => vec![b.to_string()],
+            _ => Vec::new(),
+        }
+    }
+
+    fn call_to_return(&self, _: usize, node: &Block<String>, fact: &String) -> Vec<String> {
+        match words(node).as_slice() {
+            ["call", _, _, b] if fact == b => Vec::new(),
+            _ => vec![fact.clone()],
+        }
+    }
+}
+
+impl Edges<Block<String>, Funcs> for Constants {
+    type Value = Flat<i64>;
+    type EdgeFn = Linear;
+
+    fn normal_edge(&self, _: usize, node: &Block<String>, _: &String, next: &String) -> Linear {
+        match assign(node) {
+            Some((x, Src::Const(c))) if next == x => Linear::constant(c),
+            Some((x, Src::Input)) if next == x => Linear::Top,
+            Some((x, Src::Var(_, a, b))) if next == x => Linear::Affine { a, b },
+            _ => Linear::identity(),
+        }
+    }
+
+    fn call_edge(&self, _: usize, _: &Block<String>, _: usize, _: &String, _: &String) -> Linear {
+        Linear::identity()
+    }
+
+    fn ret_edge(&self, _: usize, _: &Block<String>, _: usize, _: &String, _: &String) -> Linear {
+        Linear::identity()
+    }
+
+    fn call_to_return_edge(&self, _: usize, _: &Block<String>, _: &String, _: &String) -> Linear {
+        Linear::identity()
+    }
+}
+
+fn var(name: &str) -> String {
+    name.to_string()
+}
+
+#[test]
+fn context_sensitive() {
+    let program = Funcs::parse(&[
+        // 0: main, which calls 1 with two different constants
+        "1 -> 2 : x = 5\n2 -> 3 : call 1 x y\n3 -> 4 : z = 7\n4 -> 5 : call 1 z w\n5 : v = y + 1",
+        // 1: a linear function of its argument
+        "1 : r = p * 2 + 1",
+    ]);
+
+    let res = ide::solve(&program, &Constants, 0);
+    let exit = &res.values[&0][&BlockId(5)].after;
+
+    assert_eq!(exit.get(&var("x")), Flat::Const(5));
+    assert_eq!(exit.get(&var("y")), Flat::Const(11));
+    assert_eq!(exit.get(&var("w")), Flat::Const(15));
+    assert_eq!(exit.get(&var("v")), Flat::Const(12));
+
+    // Inside the function, the argument is different for each call
+    assert_eq!(res.values[&1][&BlockId(1)].after.get(&var("r")), Flat::Top);
+    assert_eq!(
+        res.summaries[&(1, var("p"))][&var("r")],
+        Linear::Affine { a: 2, b: 1 }
+    );
+}
+
+#[test]
+fn recursion_and_input() {
+    let program = Funcs::parse(&[
+        // 0: main
+        "1 -> 2 : a = 1\n2 -> 3 : call 1 a b\n3 -> 4 : c = input\n4 : d = c * 3 + 0",
+        // 1: a recursive function which always returns its argument
+        "1 -> 2, 3 : r = p\n2 -> 3 : call 1 p r\n3",
+    ]);
+
+    let res = ide::solve(&program, &Constants, 0);
+    let exit = &res.values[&0][&BlockId(4)].after;
+
+    assert_eq!(exit.get(&var("b")), Flat::Const(1));
+    assert_eq!(exit.get(&var("c")), Flat::Top);
+    assert_eq!(exit.get(&var("d")), Flat::Top);
+    assert_eq!(res.summaries[&(1, var("p"))][&var("r")], Linear::identity());
+}
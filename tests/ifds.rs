@@ -0,0 +1,105 @@
+//! IFDS taint analysis, which is context sensitive across calls
+
+mod program;
+
+use creek::cfg::{Block, BlockId};
+use creek::ifds::{self, gen_kill, Flows};
+use creek::lattice::PowerSet;
+use program::{words, Funcs};
+
+/// Taint analysis, where facts are tainted variables and the zero fact is the
+/// empty string. Payloads are `x = source`, `x = 0`, `x = y`, `sink x` or
+/// `call f a b`, which passes `a` to `f` as `p` and puts its result `r` into
+/// `b`.
+struct Taint;
+
+fn zero() -> String {
+    String::new()
+}
+
+impl Flows<Block<String>, Funcs> for Taint {
+    type Fact = String;
+
+    fn zero(&self) -> String {
+        zero()
+    }
+
+    fn normal(&self, _: usize, node: &Block<String>, fact: &String) -> Vec<String> {
+        match words(node).as_slice() {
+            [x, "=", "source"] => gen_kill(fact, &zero(), Some(x.to_string()), |d| d == x),
+            [x, "=", "0"] => gen_kill(fact, &zero(), None, |d| d == x),
+            [x, "=", y] if fact == y => vec![fact.clone(), x.to_string()],
+            [x, "=", _] => gen_kill(fact, &zero(), None, |d| d == x),
+            _ => vec![fact.clone()],
+        }
+    }
+
+    fn call(&self, _: usize, node: &Block<String>, _: usize, fact: &String) -> Vec<String> {
+        match words(node).as_slice() {
+            _ if fact.is_empty() => vec![zero()],
+            ["call", _, a, _] if fact == a => vec!["p".to_string()],
+            _ => Vec::new(),
+        }
+    }
+
+    fn ret(&self, _: usize, node: &Block<String>, _: usize, fact: &String) -> Vec<String> {
+        match words(node).as_slice() {
+            _ if fact.is_empty() => vec![zero()],
+            ["call", _, _, b] if fact == "r" => vec![b.to_string()],
+            _ => Vec::new(),
+        }
+    }
+
+    fn call_to_return(&self, _: usize, node: &Block<String>, fact: &String) -> Vec<String> {
+        match words(node).as_slice() {
+            ["call", _, _, b] if fact == b => Vec::new(),
+            _ => vec![fact.clone()],
+        }
+    }
+}
+
+fn set(vars: &[&str]) -> PowerSet<String> {
+    vars.iter().map(|v| v.to_string()).collect()
+}
+
+#[test]
+fn context_sensitive() {
+    let program = Funcs::parse(&[
+        // 0: main, which passes a tainted and a clean value through 1
+        "1 -> 2 : a = source\n2 -> 3 : call 1 a b\n3 -> 4 : call 1 c d\n4 : sink b",
+        // 1: the identity function
+        "1 : r = p",
+    ]);
+
+    let res = ifds::solve(&program, &Taint, 0);
+    let main = &res.facts[&0];
+
+    assert_eq!(main[&BlockId(3)].before, set(&["a", "b"]));
+    assert_eq!(main[&BlockId(4)].before, set(&["a", "b"]));
+    assert_eq!(res.facts[&1][&BlockId(1)].after, set(&["p", "r"]));
+
+    // The function was reached with `p` tainted, and with nothing tainted
+    assert_eq!(
+        res.summaries[&(1, "p".to_string())],
+        vec!["p".to_string(), "r".to_string()].into_iter().collect()
+    );
+    assert!(res.summaries[&(1, zero())].is_empty());
+}
+
+#[test]
+fn recursion() {
+    let program = Funcs::parse(&[
+        // 0: main, which only calls the recursive function with a clean value
+        // after cleaning `x`
+        "1 -> 2 : x = source\n2 -> 3 : call 1 x y\n3 -> 4 : x = 0\n4 : call 1 x z",
+        // 1: a recursive function which eventually returns its argument
+        "1 -> 2, 3 : r = p\n2 -> 3 : call 1 p r\n3",
+    ]);
+
+    let res = ifds::solve(&program, &Taint, 0);
+    let main = &res.facts[&0];
+
+    assert_eq!(main[&BlockId(2)].after, set(&["x", "y"]));
+    assert_eq!(main[&BlockId(4)].after, set(&["y"]));
+    assert!(res.summaries[&(1, "p".to_string())].contains("r"));
+}
@@ -0,0 +1,154 @@
+//! Whole-program analysis, solving functions bottom-up with summaries
+
+mod program;
+
+use creek::cfg::{Block, BlockId, NodeGraph};
+use creek::interproc::{self, CallGraph, Program};
+use creek::lattice::{Bound, Interval, Lattice, PowerSet, Widen};
+use creek::{Analyzer, Graph, HashMap, NodeInfo};
+use program::Funcs;
+
+type Assigned = PowerSet<String>;
+
+/// Find the variables which may have been assigned, including by calls, at
+/// every point of every function
+fn assigned(program: &Funcs) -> interproc::Solution<usize, BlockId, Assigned, Assigned> {
+    let solve = |func, graph: &NodeGraph<String>, summaries: &interproc::Summaries<_, Assigned>| {
+        let trans = |block: &Block<String>, mut fact: Assigned| match program.callees(func, block) {
+            [] => {
+                fact.insert(block.data.clone());
+                fact
+            }
+            callees => callees
+                .iter()
+                .fold(fact, |fact, callee| fact.join(summaries.get(*callee))),
+        };
+
+        Analyzer::new_forward_lattice(trans).solve(graph)
+    };
+
+    let summarize = |func, res: &HashMap<BlockId, NodeInfo<Assigned>>| {
+        let exit = program.graph(func).get_exit();
+        res[&exit].after.clone()
+    };
+
+    interproc::solve(program, Assigned::empty(), solve, summarize)
+}
+
+fn set(vars: &[&str]) -> Assigned {
+    vars.iter().map(|v| v.to_string()).collect()
+}
+
+#[test]
+fn call_graph() {
+    // 0 calls 1 and 2, 1 and 2 call each other, and 3 calls itself
+    let program = Funcs::parse(&["1 : call 1,2", "1 : call 2", "1 : call 1", "1 : call 3"]);
+    let calls = CallGraph::new(&program);
+
+    assert_eq!(calls.callees(0), &[1, 2]);
+    assert_eq!(calls.callers(2), &[0, 1]);
+    assert_eq!(calls.sccs(), vec![vec![1, 2], vec![0], vec![3]]);
+    assert!(calls.is_recursive(1));
+    assert!(calls.is_recursive(3));
+    assert!(!calls.is_recursive(0));
+}
+
+#[test]
+fn call_sites() {
+    let program = Funcs::parse(&[
+        "1 -> 2 : a
+2 -> 3 : call 1
+3 : b",
+        "1 : c",
+    ]);
+
+    assert_eq!(program.call_sites(0), vec![BlockId(2)]);
+    assert_eq!(program.call_sites(1), Vec::new());
+    assert_eq!(program.return_sites(0, BlockId(2)), vec![BlockId(3)]);
+}
+
+/// Calls to functions outside the program are left out of the call graph,
+/// and use the initial summary
+#[test]
+fn external() {
+    let program = Funcs::parse(&[
+        "1 -> 2 : a
+2 : call 1,9",
+        "1 : b",
+    ]);
+    let calls = CallGraph::new(&program);
+
+    assert_eq!(calls.callees(0), &[1]);
+    assert_eq!(calls.callers(9), &[] as &[usize]);
+    assert_eq!(calls.sccs(), vec![vec![1], vec![0]]);
+
+    let res = assigned(&program);
+    assert_eq!(res.summaries[&0], set(&["a", "b"]));
+    assert!(!res.summaries.contains_key(&9));
+}
+
+#[test]
+fn summaries() {
+    let program = Funcs::parse(&[
+        // 0: main, which calls 1 on one branch
+        "1 -> 2, 3 : a\n2 -> 4 : call 1\n3 -> 4 : b\n4 : c",
+        // 1, which calls 2
+        "1 -> 2 : d\n2 : call 2",
+        // 2, a leaf
+        "1 : e",
+        // 3 and 4 call each other
+        "1 -> 2 : f\n2 : call 4",
+        "1 -> 2 : call 3\n2 : g",
+    ]);
+
+    let res = assigned(&program);
+
+    assert_eq!(res.summaries[&2], set(&["e"]));
+    assert_eq!(res.summaries[&1], set(&["d", "e"]));
+    assert_eq!(res.summaries[&0], set(&["a", "b", "c", "d", "e"]));
+    assert_eq!(res.facts[&0][&BlockId(2)].after, set(&["a", "d", "e"]));
+
+    assert_eq!(res.summaries[&3], set(&["f", "g"]));
+    assert_eq!(res.summaries[&4], set(&["f", "g"]));
+}
+
+/// A function which calls itself any number of times has a summary which
+/// grows forever, unless it is widened
+#[test]
+fn widening() {
+    // Counts how many times it is entered
+    let program = Funcs::parse(&["1 -> 2, 3 : inc\n2 -> 3 : call 0\n3 : end"]);
+
+    let solve = |func, graph: &NodeGraph<String>, summaries: &interproc::Summaries<_, Interval>| {
+        let trans = |block: &Block<String>, count: Interval| match program.callees(func, block) {
+            [] if block.data == "inc" => count + Interval::constant(1),
+            [] => count,
+            callees => callees
+                .iter()
+                .fold(count, |count, callee| count + *summaries.get(*callee)),
+        };
+
+        Analyzer::new_forward_lattice(trans)
+            .with_entry_fact(Interval::constant(0))
+            .solve(graph)
+    };
+
+    let summarize = |func, res: &HashMap<BlockId, NodeInfo<Interval>>| {
+        let exit = program.graph(func).get_exit();
+        res[&exit].after
+    };
+
+    let res = interproc::solve_with_widening(
+        &program,
+        Interval::Empty,
+        Interval::widen,
+        solve,
+        summarize,
+    );
+
+    assert_eq!(res.summaries[&0], Interval::new(1, Bound::PosInf));
+    assert_eq!(
+        res.facts[&0][&BlockId(2)].after,
+        Interval::new(2, Bound::PosInf)
+    );
+}
@@ -0,0 +1,161 @@
+//! Intervals, widening, and solving with widening
+
+use std::cmp::Ordering;
+
+use creek::analyses::intervals;
+use creek::cfg::{Block, BlockId, Variable};
+use creek::lattice::{join_all, Bound, Bounded, Interval, Lattice, Widen};
+use creek::{text, Analyzer};
+
+#[test]
+fn arithmetic() {
+    let a = Interval::new(-2, 3);
+    let b = Interval::new(1, Bound::PosInf);
+
+    assert_eq!(a + b, Interval::new(-1, Bound::PosInf));
+    assert_eq!(a - b, Interval::new(Bound::NegInf, 2));
+    assert_eq!(a * Interval::new(-1, 2), Interval::new(-4, 6));
+    assert_eq!(a * b, Interval::full());
+    assert_eq!(Interval::constant(0) * b, Interval::constant(0));
+    assert_eq!(a + Interval::Empty, Interval::Empty);
+
+    assert_eq!(a.join(&Interval::constant(7)), Interval::new(-2, 7));
+    assert_eq!(a.meet(&b), Interval::new(1, 3));
+    assert_eq!(a.meet(&Interval::new(5, 6)), Interval::Empty);
+    assert_eq!(join_all(vec![a, b]), Interval::new(-2, Bound::PosInf));
+    assert_eq!(b.restrict(Ordering::Less, 10), Interval::new(1, 9));
+
+    assert_eq!(
+        Interval::new(i64::MAX - 1, i64::MAX) + b,
+        Interval::new(i64::MAX, Bound::PosInf)
+    );
+}
+
+/// Arithmetic at the edges of `i64` stays a valid interval, and never panics
+#[test]
+fn overflow() {
+    let min = Interval::constant(i64::MIN);
+    let max = Interval::constant(i64::MAX);
+
+    // -i64::MIN doesn't fit, so it's only known to be above i64::MAX
+    assert_eq!(-min, Interval::new(i64::MAX, Bound::PosInf));
+    assert_eq!(-Interval::new(i64::MIN, 0), Interval::new(0, Bound::PosInf));
+    assert_eq!(-max, Interval::constant(-i64::MAX));
+    assert_eq!(
+        Interval::constant(0) - min,
+        Interval::new(i64::MAX, Bound::PosInf)
+    );
+    assert_eq!(-min, min * Interval::constant(-1));
+    assert_eq!(Interval::full() - min, Interval::full());
+    assert_eq!(max - min, Interval::new(i64::MAX, Bound::PosInf));
+    assert_eq!(min - max, Interval::new(Bound::NegInf, i64::MIN));
+    assert_eq!(min + min, Interval::new(Bound::NegInf, i64::MIN));
+    assert_eq!(
+        min * Interval::constant(-1),
+        Interval::new(i64::MAX, Bound::PosInf)
+    );
+    assert_eq!(min * max, Interval::new(Bound::NegInf, i64::MIN));
+
+    assert_eq!(min.restrict(Ordering::Less, i64::MIN), Interval::Empty);
+    assert_eq!(max.restrict(Ordering::Greater, i64::MAX), Interval::Empty);
+
+    // The bounds of an interval can only be made in order
+    match Interval::new(1, 2) {
+        Interval::Range(r) => assert_eq!((r.lo(), r.hi()), (Bound::Int(1), Bound::Int(2))),
+        Interval::Empty => panic!("1..=2 is empty"),
+    }
+}
+
+#[test]
+fn widening() {
+    let a = Interval::new(0, 1);
+
+    assert_eq!(
+        a.widen(&Interval::new(0, 2)),
+        Interval::new(0, Bound::PosInf)
+    );
+    assert_eq!(
+        a.widen(&Interval::new(-1, 1)),
+        Interval::new(Bound::NegInf, 1)
+    );
+    assert_eq!(a.widen(&a), a);
+    assert_eq!(Interval::Empty.widen(&a), a);
+
+    let thresholds = [-10, 0, 100];
+    assert_eq!(
+        a.widen_with(&Interval::new(0, 2), &thresholds),
+        Interval::new(0, 100)
+    );
+    assert_eq!(
+        a.widen_with(&Interval::new(-1, 1), &thresholds),
+        Interval::new(-10, 1)
+    );
+    assert_eq!(
+        a.widen_with(&Interval::new(0, 101), &thresholds),
+        Interval::new(0, Bound::PosInf)
+    );
+}
+
+/// A counter which goes up forever would never stabilize without widening,
+/// and one which stops at 100 is only found to do so with a threshold
+///
+/// ```plain
+/// +-1-----+    +-2------+    +-3-----+
+/// | i = 0 |--->| i += 1 |--->| exit  |
+/// +-------+    +--------+    +-------+
+///               ^      |
+///               +------+
+/// ```
+#[test]
+fn counter() {
+    let graph = text::parse("1 -> 2 : i = 0\n2 -> 2, 3 : i += 1\n3").unwrap();
+
+    let trans = |block: &Block<String>, i: Interval| match block.data.as_str() {
+        "i = 0" => Interval::constant(0),
+        _ => i + Interval::constant(1),
+    };
+
+    let res = Analyzer::new_forward(Interval::bottom(), trans, join_all)
+        .with_widening(Interval::widen)
+        .solve(&graph);
+    assert_eq!(res[&BlockId(3)].before, Interval::new(1, Bound::PosInf));
+
+    let saturating = |block: &Block<String>, i: Interval| match block.data.as_str() {
+        "i = 0" => Interval::constant(0),
+        _ => (i + Interval::constant(1)).restrict(Ordering::Less, 101),
+    };
+
+    let res = Analyzer::new_forward(Interval::bottom(), saturating, join_all)
+        .with_widening(Interval::widen)
+        .solve(&graph);
+    assert_eq!(res[&BlockId(2)].before, Interval::new(0, Bound::PosInf));
+
+    let res = Analyzer::new_forward(Interval::bottom(), saturating, join_all)
+        .with_widening(|prev: &Interval, next: &Interval| prev.widen_with(next, &[100]))
+        .solve(&graph);
+    assert_eq!(res[&BlockId(2)].before, Interval::new(0, 100));
+    assert_eq!(res[&BlockId(3)].before, Interval::new(1, 100));
+}
+
+/// The bundled analysis knows that values which may wrap could be anything
+#[test]
+fn statements() {
+    let graph = text::parse_statements(
+        "
+        1 -> 2, 3 : one = 1; i = 0
+        2 -> 4    : x = 1
+        3 -> 4    : x = 5
+        4 -> 4, 5 : y = x * x; i = i + one
+        5
+        ",
+    )
+    .unwrap();
+
+    let res = intervals::solve(&graph.graph);
+    let var = |name: &str| Variable(graph.names.iter().position(|n| n == name).unwrap());
+    let exit = &res[&BlockId(5)].before;
+
+    assert_eq!(exit.get(&var("x")), Interval::new(1, 5));
+    assert_eq!(exit.get(&var("y")), Interval::new(1, 25));
+    assert_eq!(exit.get(&var("i")), Interval::full());
+}
@@ -0,0 +1,70 @@
+//! Lattice combinators, and analyses which only provide `trans`
+
+use creek::analyses::{live, DefUse};
+use creek::cfg::{Block, Statement, Variable};
+use creek::lattice::{Bounded, Dual, Flat, Lattice, Lifted, Must, PowerSet, Product};
+use creek::{text, Analyzer};
+
+#[test]
+fn combinators() {
+    let a: PowerSet<u8> = vec![1, 2].into_iter().collect();
+    let b: PowerSet<u8> = vec![2, 3].into_iter().collect();
+    assert_eq!(a.join(&b), vec![1, 2, 3].into_iter().collect());
+    assert_eq!(a.meet(&b), vec![2].into_iter().collect());
+    assert_eq!(PowerSet::<u8>::top().join(&a), PowerSet::all());
+
+    let a: PowerSet<u8, Must> = vec![1, 2].into_iter().collect();
+    let b: PowerSet<u8, Must> = vec![2, 3].into_iter().collect();
+    assert_eq!(a.join(&b), vec![2].into_iter().collect());
+    assert_eq!(PowerSet::<u8, Must>::bottom().join(&a), a);
+    assert!(PowerSet::<u8, Must>::bottom().contains(&7));
+
+    let one = Flat::Const(1);
+    let two = Flat::Const(2);
+    assert_eq!(Dual(one).join(&Dual(two)), Dual(Flat::Bottom));
+    assert_eq!(Dual::<Flat<i32>>::bottom(), Dual(Flat::Top));
+    assert_eq!(Dual(one).meet(&Dual(two)), Dual(Flat::Top));
+
+    let pair = Product(one, Lifted::Lift(Flat::<i32>::Bottom));
+    let joined = pair.join(&Product::bottom());
+    assert_eq!(joined, pair);
+    assert_eq!(joined.join(&Product(two, Lifted::Bottom)).0, Flat::Top);
+    assert_ne!(Lifted::Lift(Flat::<i32>::Bottom), Lifted::bottom());
+    assert_eq!(Lifted::<Flat<i32>>::top(), Lifted::Lift(Flat::Top));
+}
+
+/// Liveness built out of a `PowerSet` only needs `trans`, and agrees with the
+/// bundled analysis
+#[test]
+fn power_set_liveness() {
+    let graph = text::parse_statements(
+        "
+        1 -> 2    : a = 1; b = 2
+        2 -> 2, 3 : c = a + b; a = c
+        3         : d = a * c
+        ",
+    )
+    .unwrap();
+
+    let trans = |node: &Block<Vec<Statement>>, mut fact: PowerSet<Variable>| {
+        for stmt in (0..node.stmt_count()).rev() {
+            for var in node.defs(stmt) {
+                fact.remove(var);
+            }
+
+            for var in node.uses(stmt) {
+                fact.insert(*var);
+            }
+        }
+
+        fact
+    };
+
+    let res = Analyzer::new_backward_lattice(trans).solve(&graph.graph);
+    let expected = live::solve(&graph.graph);
+
+    for (id, info) in expected {
+        let live = info.before.live.into_iter().collect();
+        assert_eq!(res[&id].before, live);
+    }
+}
@@ -46,6 +46,10 @@ fn trans(block: &Block, fact: LivenessFact) -> LivenessFact {
                 killed.insert(*var);
                 used.insert(*war);
             }
+            Statement::ExprAssign(var, expr) => {
+                killed.insert(*var);
+                used.extend(expr.args.iter().copied());
+            }
         }
     }
 
@@ -0,0 +1,90 @@
+//! The fixed point matches the meet over all paths for distributive problems,
+//! and the difference shows up for ones which aren't
+
+use creek::analyses::{constants, live, reaching};
+use creek::cfg::BlockId;
+use creek::lattice::Lattice;
+use creek::{text, Analyzer, HashSet};
+
+const DIAMOND: &str = "
+1 -> 2, 3    : x = 0
+2 -> 4       : a = 1; b = 2
+3 -> 4       : a = 2; b = 1
+4            : c = a + b; d = c
+";
+
+#[test]
+fn distributive() {
+    let graph = text::parse_statements(DIAMOND).unwrap();
+    let graph = &graph.graph;
+
+    let mut reaching = Analyzer::new_forward(
+        reaching::ReachingDefs::new(HashSet::default()),
+        reaching::trans,
+        reaching::join,
+    );
+    assert_eq!(reaching.solve_mop(graph, 3), reaching::solve(graph));
+    assert_eq!(reaching.check_mop(graph, 3), Vec::new());
+
+    let mut live = Analyzer::new_backward(
+        live::LiveVars::new(HashSet::default()),
+        live::trans,
+        live::join,
+    );
+    assert_eq!(live.check_mop(graph, 3), Vec::new());
+}
+
+#[test]
+fn unreachable() {
+    // Nothing reaches 3, but its definition still reaches 2
+    let graph = text::parse_statements(
+        "
+        1 -> 2 : x = 0
+        2      : y = x
+        3 -> 2 : x = 1
+        ",
+    )
+    .unwrap();
+    let graph = &graph.graph;
+
+    let mut reaching = Analyzer::new_forward(
+        reaching::ReachingDefs::new(HashSet::default()),
+        reaching::trans,
+        reaching::join,
+    );
+    assert_eq!(reaching.solve_mop(graph, 2), reaching::solve(graph));
+    assert_eq!(reaching.check_mop(graph, 2), Vec::new());
+
+    // Nothing reaches the loop between 3 and 4 either
+    let graph = text::parse_statements(
+        "
+        1 -> 2    : x = 0
+        2         : y = x
+        3 -> 4, 2 : x = 1
+        4 -> 3    : z = 2
+        ",
+    )
+    .unwrap();
+    let graph = &graph.graph;
+
+    assert_eq!(reaching.check_mop(graph, 4), Vec::new());
+}
+
+#[test]
+fn constants_are_not_distributive() {
+    let graph = text::parse_statements(DIAMOND).unwrap();
+    let graph = &graph.graph;
+
+    let mut constants = Analyzer::new_forward_lattice(constants::trans);
+    let diffs = constants.check_mop(graph, 3);
+
+    // Only the join point loses `c = 3`, since `a` and `b` differ on the way
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].id, BlockId(4));
+    assert_eq!(diffs[0].mfp.before, diffs[0].mop.before);
+    assert_ne!(diffs[0].mfp.after, diffs[0].mop.after);
+
+    // Paths which are too short leave the join point out entirely
+    let mop = constants.solve_mop(graph, 2);
+    assert_eq!(mop[&BlockId(4)].before, constants::Env::bottom());
+}
@@ -0,0 +1,154 @@
+//! Observers are told about every step the solver takes. These tests run a
+//! small definite assignment problem and check that the events line up with
+//! the final counts.
+
+mod cfg;
+
+use cfg::*;
+use creek::{Analyzer, Fact, Observer, Stats};
+use fnv::FnvHashSet;
+
+#[derive(Clone, Debug, PartialEq)]
+struct AssignmentFact {
+    uninit: FnvHashSet<Variable>,
+}
+
+impl Fact for AssignmentFact {}
+
+fn trans(block: &Block, mut fact: AssignmentFact) -> AssignmentFact {
+    for stmt in block.stmts.iter() {
+        match stmt {
+            Statement::Declare(var) => {
+                fact.uninit.insert(*var);
+            }
+            Statement::ConstAssign(var, _)
+            | Statement::VarAssign(var, _)
+            | Statement::ExprAssign(var, _) => {
+                fact.uninit.remove(var);
+            }
+        }
+    }
+
+    fact
+}
+
+fn join(facts: Vec<AssignmentFact>) -> AssignmentFact {
+    let mut uninit = FnvHashSet::default();
+
+    for fact in facts {
+        uninit.extend(fact.uninit);
+    }
+
+    AssignmentFact { uninit }
+}
+
+#[derive(Default)]
+struct Recorder {
+    visited: Vec<BlockId>,
+    changed: Vec<BlockId>,
+    enqueued: Vec<BlockId>,
+    stats: Option<Stats<BlockId>>,
+}
+
+impl Observer<Block, AssignmentFact> for Recorder {
+    fn node_visited(&mut self, id: BlockId) {
+        self.visited.push(id);
+    }
+
+    fn fact_changed(&mut self, id: BlockId, old: &AssignmentFact, new: &AssignmentFact) {
+        assert_ne!(old, new);
+        self.changed.push(id);
+    }
+
+    fn node_enqueued(&mut self, id: BlockId, _len: usize) {
+        self.enqueued.push(id);
+    }
+
+    fn finished(&mut self, stats: &Stats<BlockId>) {
+        self.stats = Some(stats.clone());
+    }
+}
+
+/// ```plain
+/// +-1-----+
+/// | var a |
+/// +-------+
+///     |
+///     v
+/// +-2-----+
+/// | a = 0 |<-+
+/// +-------+  |
+///   |   |    |
+///   |   +----+
+///   v
+/// +-3-----+
+/// | b = a |
+/// +-------+
+/// ```
+fn one_loop() -> NodeGraph {
+    let mut graph = NodeGraph::new(block! {
+        1;
+        from => ;
+        to => 2;
+        (var 0)
+    });
+
+    graph.insert(block! {
+        2;
+        from => 1, 2;
+        to => 2, 3;
+        (0 = 0)
+    });
+
+    graph.insert_exit(block! {
+        3;
+        from => 2;
+        to => ;
+        (1 = var 0)
+    });
+
+    graph
+}
+
+#[test]
+fn events_match_stats() {
+    let graph = one_loop();
+    let top = AssignmentFact {
+        uninit: FnvHashSet::default(),
+    };
+
+    let mut recorder = Recorder::default();
+    let mut analyzer = Analyzer::new_forward(top, trans, join);
+    let res = analyzer.solve_observed(&graph, &mut recorder);
+
+    let stats = recorder.stats.expect("finished was not called");
+    assert_eq!(recorder.visited.len(), stats.total_visits());
+    assert_eq!(recorder.visited.len(), stats.trans_calls);
+    assert_eq!(recorder.visited.len(), stats.join_calls);
+    assert!(stats.max_worklist_len >= 3);
+
+    // Every node starts out on the worklist, and is visited once more for
+    // every time it is enqueued
+    for id in &[BlockId(1), BlockId(2), BlockId(3)] {
+        let enqueued = recorder.enqueued.iter().filter(|e| *e == id).count();
+        assert_eq!(stats.visits[id], enqueued + 1);
+    }
+
+    // Block 1 goes from {} to {a}, which must be reported
+    assert!(recorder.changed.contains(&BlockId(1)));
+    assert_eq!(res.len(), 3);
+}
+
+#[test]
+fn observed_solve_matches_solve() {
+    let graph = one_loop();
+    let top = AssignmentFact {
+        uninit: FnvHashSet::default(),
+    };
+
+    let mut analyzer = Analyzer::new_forward(top, trans, join);
+    let plain = analyzer.solve(&graph);
+    let observed = analyzer.solve_observed(&graph, &mut ());
+
+    assert_eq!(plain, observed);
+}
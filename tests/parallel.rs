@@ -0,0 +1,141 @@
+//! Solving in parallel gives exactly the same facts as solving on one thread
+
+#![cfg(feature = "rayon")]
+
+use creek::analyses::{constants, live, reaching};
+use creek::cfg::{Block, BlockId, NodeGraph};
+use creek::lattice::{Interval, PowerSet, Widen};
+use creek::{text, Analyzer, HashSet};
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+const LOOPS: &str = "
+1 -> 2, 5    : a = 1; b = 2
+2 -> 3       : c = a + b
+3 -> 2, 4    : a = c
+4 -> 8       : d = a
+5 -> 6       : e = b
+6 -> 7       : f = e * b
+7 -> 6, 8    : e = f
+8            : g = d + e
+";
+
+#[test]
+fn same_fixpoint() {
+    let graph = text::parse_statements(LOOPS).unwrap();
+    let graph = &graph.graph;
+
+    let mut constants = Analyzer::new_forward_lattice(constants::trans);
+    assert_eq!(constants.solve_parallel(graph), constants::solve(graph));
+
+    let mut reaching = Analyzer::new_forward(
+        reaching::ReachingDefs::new(HashSet::default()),
+        reaching::trans,
+        reaching::join,
+    );
+    assert_eq!(reaching.solve_parallel(graph), reaching::solve(graph));
+
+    let mut live = Analyzer::new_backward(
+        live::LiveVars::new(HashSet::default()),
+        live::trans,
+        live::join,
+    );
+    assert_eq!(live.solve_parallel(graph), live::solve(graph));
+}
+
+/// A long line of small loops, each of which is its own component
+fn chain() -> NodeGraph<Option<usize>> {
+    let mut graph = NodeGraph::new(Block::new(BlockId(0), None));
+
+    for i in 1..=1000 {
+        graph.insert(Block::new(BlockId(2 * i - 1), Some(i)));
+        graph.insert(Block::new(BlockId(2 * i), None));
+        graph.add_edge(BlockId(2 * i - 2), BlockId(2 * i - 1));
+        graph.add_edge(BlockId(2 * i - 1), BlockId(2 * i));
+        graph.add_edge(BlockId(2 * i), BlockId(2 * i - 1));
+    }
+
+    graph.set_exit(BlockId(2000));
+    graph
+}
+
+#[test]
+fn many_components() {
+    let graph = chain();
+
+    // The last digits of the loops seen so far
+    let trans = |block: &Block<Option<usize>>, mut seen: PowerSet<usize>| {
+        if let Some(i) = block.data {
+            seen.insert(i % 10);
+        }
+
+        seen
+    };
+
+    let mut analyzer = Analyzer::new_forward_lattice(trans);
+    let res = analyzer.solve_parallel(&graph);
+    assert_eq!(res, analyzer.solve(&graph));
+    assert_eq!(res[&BlockId(6)].after, (1..=3).collect());
+
+    // Widening depends on the order, so it isn't done in parallel
+    let count = |block: &Block<Option<usize>>, i: Interval| match block.data {
+        Some(_) => i + Interval::constant(1),
+        None if block.id == BlockId(0) => Interval::constant(0),
+        None => i,
+    };
+
+    let mut analyzer = Analyzer::new_forward_lattice(count).with_widening(Interval::widen);
+    assert_eq!(analyzer.solve_parallel(&graph), analyzer.solve(&graph));
+}
+
+#[test]
+fn components_overlap() {
+    // 0 -> 1 -> 2
+    // |
+    // +--> 3
+    let mut graph = NodeGraph::new(Block::new(BlockId(0), ()));
+    for i in 1..=3 {
+        graph.insert(Block::new(BlockId(i), ()));
+    }
+
+    graph.add_edge(BlockId(0), BlockId(1));
+    graph.add_edge(BlockId(1), BlockId(2));
+    graph.add_edge(BlockId(0), BlockId(3));
+    graph.set_exit(BlockId(2));
+
+    // 3 blocks until 2 is visited, which is two components away from 0 where 3
+    // is only one, so 2 is only ever visited if it doesn't wait for 3 to be
+    // done. The timeout only stops a broken schedule from hanging forever.
+    let visited = Mutex::new(false);
+    let signal = Condvar::new();
+    let timed_out = Mutex::new(false);
+    let trans = |block: &Block<()>, mut seen: PowerSet<usize>| {
+        match block.id {
+            BlockId(2) => {
+                *visited.lock().unwrap() = true;
+                signal.notify_all();
+            }
+            BlockId(3) => {
+                let guard = visited.lock().unwrap();
+                let wait = signal
+                    .wait_timeout_while(guard, Duration::from_secs(60), |visited| !*visited)
+                    .unwrap()
+                    .1;
+                *timed_out.lock().unwrap() = wait.timed_out();
+            }
+            _ => {}
+        }
+
+        seen.insert(block.id.0);
+        seen
+    };
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(2)
+        .build()
+        .unwrap();
+
+    let res = pool.install(|| Analyzer::new_forward_lattice(trans).solve_parallel(&graph));
+    assert!(!*timed_out.lock().unwrap());
+    assert_eq!(res[&BlockId(2)].after, vec![0, 1, 2].into_iter().collect());
+}
@@ -0,0 +1,142 @@
+//! Lazy code motion should move partially redundant computations to where
+//! they are fully redundant, splitting critical edges when needed.
+
+use creek::cfg::{BinOp, BlockId, Expr, Variable};
+use creek::{pre, text, Graph, HashMap, HashSet};
+
+fn add(a: usize, b: usize) -> Expr {
+    Expr {
+        op: BinOp::Add,
+        args: [Variable(a), Variable(b)],
+    }
+}
+
+fn sets<K: Copy + Eq + std::hash::Hash>(pairs: &[(K, Expr)]) -> HashMap<K, HashSet<Expr>> {
+    let mut res: HashMap<K, HashSet<Expr>> = HashMap::default();
+    for (k, e) in pairs {
+        res.entry(*k).or_default().insert(*e);
+    }
+
+    res
+}
+
+/// ```plain
+///       +-1-+
+///       +---+
+///        | |
+///        | +------+
+///        v        v
+/// +-2---------+  +-3-+
+/// | c = a + b |  +---+
+/// +-----------+   |
+///        |        |
+///        v        v
+///       +-4---------+
+///       | d = a + b |
+///       +-----------+
+/// ```
+#[test]
+fn diamond() {
+    let mut parsed = text::parse_statements(
+        "
+1 -> 2, 3
+2 -> 4    : c = a + b
+3 -> 4
+4         : d = a + b
+",
+    )
+    .unwrap();
+
+    let placement = pre::run(&mut parsed.graph);
+    let e = add(1, 2);
+
+    // There are no critical edges, so block 3 takes the computation
+    assert_eq!(4, parsed.graph.len());
+    assert_eq!(sets(&[(BlockId(2), e), (BlockId(3), e)]), placement.insert);
+    assert!(placement.edge_insert.is_empty());
+    assert_eq!(sets(&[(BlockId(2), e), (BlockId(4), e)]), placement.delete);
+}
+
+/// ```plain
+/// +-1-+
+/// +---+
+///  | |
+///  | v
+///  | +-2---------+
+///  | | c = a + b |
+///  | +-----------+
+///  |   |
+///  v   v
+/// +-3---------+
+/// | d = a + b |
+/// +-----------+
+/// ```
+#[test]
+fn critical_edge() {
+    let mut parsed = text::parse_statements(
+        "
+1 -> 2, 3
+2 -> 3    : c = a + b
+3         : d = a + b
+",
+    )
+    .unwrap();
+
+    let placement = pre::run(&mut parsed.graph);
+    let e = add(1, 2);
+
+    // The edge from 1 to 3 is split by a new block 4
+    let graph = &parsed.graph;
+    assert_eq!(4, graph.len());
+    assert_eq!(&[BlockId(2), BlockId(4)], graph.get_succs(BlockId(1)));
+    assert_eq!(&[BlockId(4), BlockId(2)], graph.get_preds(BlockId(3)));
+
+    assert_eq!(sets(&[(BlockId(2), e)]), placement.insert);
+    assert_eq!(
+        sets(&[((BlockId(1), BlockId(3)), e)]),
+        placement.edge_insert
+    );
+    assert_eq!(sets(&[(BlockId(2), e), (BlockId(3), e)]), placement.delete);
+}
+
+/// An expression computed in a loop whose operands don't change in it is
+/// hoisted out of the loop
+///
+/// ```plain
+/// +-1-----+
+/// | i = 0 |
+/// +-------+
+///     |
+///     v
+/// +-2---------+
+/// | c = a + b |<-+
+/// | i = i + c |  |
+/// +-----------+  |
+///   |   |        |
+///   |   +--------+
+///   v
+/// +-3-+
+/// +---+
+/// ```
+#[test]
+fn loop_invariant() {
+    let mut parsed = text::parse_statements(
+        "
+1 -> 2    : i = 0
+2 -> 2, 3 : c = a + b; i = i + c
+3
+",
+    )
+    .unwrap();
+
+    let placement = pre::run(&mut parsed.graph);
+    let invariant = add(2, 3);
+
+    // The back edge from 2 to itself is critical, and so is split, but the
+    // computation is hoisted all the way into block 1. `i + c` isn't
+    // evaluated before `c` is assigned, so it stays where it is.
+    assert_eq!(4, parsed.graph.len());
+    assert_eq!(sets(&[(BlockId(1), invariant)]), placement.insert);
+    assert!(placement.edge_insert.is_empty());
+    assert_eq!(sets(&[(BlockId(2), invariant)]), placement.delete);
+}
@@ -0,0 +1,62 @@
+//! A simple program made of functions written in the text format. A block
+//! whose payload starts with `call f,g` calls functions `f` and `g`, where
+//! functions are numbered in the order they are given.
+
+#![allow(dead_code)]
+
+use creek::cfg::{Block, BlockId, NodeGraph};
+use creek::interproc::Program;
+use creek::{text, HashMap};
+
+pub struct Funcs {
+    ids: Vec<usize>,
+    graphs: Vec<NodeGraph<String>>,
+    calls: HashMap<(usize, BlockId), Vec<usize>>,
+}
+
+impl Funcs {
+    pub fn parse(srcs: &[&str]) -> Self {
+        let graphs: Vec<_> = srcs.iter().map(|src| text::parse(src).unwrap()).collect();
+        let mut calls = HashMap::default();
+
+        for (func, graph) in graphs.iter().enumerate() {
+            for block in graph.blocks() {
+                if let Some(rest) = block.data.strip_prefix("call ") {
+                    let callees = rest.split(' ').next().unwrap();
+                    let callees = callees.split(',').map(|c| c.parse().unwrap()).collect();
+                    calls.insert((func, block.id), callees);
+                }
+            }
+        }
+
+        Self {
+            ids: (0..graphs.len()).collect(),
+            graphs,
+            calls,
+        }
+    }
+}
+
+impl Program<Block<String>> for Funcs {
+    type FuncId = usize;
+    type Graph = NodeGraph<String>;
+
+    fn functions(&self) -> &[usize] {
+        &self.ids
+    }
+
+    fn graph(&self, func: usize) -> &NodeGraph<String> {
+        &self.graphs[func]
+    }
+
+    fn callees(&self, func: usize, node: &Block<String>) -> &[usize] {
+        self.calls
+            .get(&(func, node.id))
+            .map_or(&[], |c| c.as_slice())
+    }
+}
+
+/// Split a payload into words
+pub fn words(block: &Block<String>) -> Vec<&str> {
+    block.data.split_whitespace().collect()
+}
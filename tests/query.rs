@@ -0,0 +1,67 @@
+//! Querying single nodes only solves the nodes they depend on, and gives the
+//! same facts as solving the whole graph
+
+use creek::analyses::{constants, live};
+use creek::cfg::BlockId;
+use creek::{text, Analyzer, HashSet};
+
+const LOOP: &str = "
+1 -> 2       : a = 1; b = 2
+2 -> 3       : c = a + b
+3 -> 2, 4    : a = c
+4 -> 5       : d = a
+5            : e = b
+";
+
+fn ids(ids: &[usize]) -> HashSet<BlockId> {
+    ids.iter().map(|id| BlockId(*id)).collect()
+}
+
+#[test]
+fn forward() {
+    let graph = text::parse_statements(LOOP).unwrap();
+    let graph = &graph.graph;
+    let all = constants::solve(graph);
+
+    let mut analyzer = Analyzer::new_forward_lattice(constants::trans);
+    let mut query = analyzer.query(graph);
+
+    // The loop is solved, but nothing after it
+    assert_eq!(query.get(BlockId(3)), &all[&BlockId(3)]);
+    assert_eq!(
+        query.solved().copied().collect::<HashSet<_>>(),
+        ids(&[1, 2, 3])
+    );
+
+    assert_eq!(query.before(BlockId(5)), &all[&BlockId(5)].before);
+    assert_eq!(query.solved().count(), 5);
+}
+
+#[test]
+fn backward() {
+    let graph = text::parse_statements(LOOP).unwrap();
+    let graph = &graph.graph;
+    let all = live::solve(graph);
+
+    let mut analyzer = Analyzer::new_backward(
+        live::LiveVars::new(HashSet::default()),
+        live::trans,
+        live::join,
+    );
+    let mut query = analyzer.query(graph);
+
+    assert_eq!(query.after(BlockId(4)), &all[&BlockId(4)].after);
+    assert_eq!(
+        query.solved().copied().collect::<HashSet<_>>(),
+        ids(&[4, 5])
+    );
+
+    // Earlier answers are reused rather than solved again
+    let visits = query.stats().visits.clone();
+    assert_eq!(query.before(BlockId(2)), &all[&BlockId(2)].before);
+    assert_eq!(query.stats().visits[&BlockId(5)], visits[&BlockId(5)]);
+
+    for id in 1..=5 {
+        assert_eq!(query.get(BlockId(id)), &all[&BlockId(id)]);
+    }
+}
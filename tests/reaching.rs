@@ -0,0 +1,108 @@
+//! Reaching definitions, and the def-use and use-def chains built from them.
+
+use creek::analyses::reaching::{self, Def, Use};
+use creek::cfg::{BlockId, Variable};
+use creek::{text, HashSet};
+
+/// ```plain
+/// +-1-----+
+/// | a = 0 |
+/// | b = 1 |
+/// +-------+
+///     |
+///     v
+/// +-2-----+
+/// | c = a |<-+
+/// | a = b |  |
+/// +-------+  |
+///   |   |    |
+///   |   +----+
+///   v
+/// +-3-----+
+/// | d = a |
+/// +-------+
+/// ```
+const ONE_LOOP: &str = "
+1 -> 2    : a = 0; b = 1
+2 -> 2, 3 : c = a; a = b
+3         : d = a
+";
+
+fn def(node: usize, stmt: usize, var: usize) -> Def<BlockId, Variable> {
+    Def {
+        node: BlockId(node),
+        stmt,
+        var: Variable(var),
+    }
+}
+
+fn use_(node: usize, stmt: usize, var: usize) -> Use<BlockId, Variable> {
+    Use {
+        node: BlockId(node),
+        stmt,
+        var: Variable(var),
+    }
+}
+
+#[test]
+fn reaching_one_loop() {
+    let parsed = text::parse_statements(ONE_LOOP).unwrap();
+    let res = reaching::solve(&parsed.graph);
+
+    let [a, b, c, d] = [0, 1, 2, 3];
+    let defs = |defs: &[Def<BlockId, Variable>]| -> HashSet<_> { defs.iter().copied().collect() };
+
+    assert_eq!(defs(&[]), res[&BlockId(1)].before.defs);
+    assert_eq!(
+        defs(&[def(1, 0, a), def(1, 1, b)]),
+        res[&BlockId(1)].after.defs
+    );
+    assert_eq!(
+        defs(&[def(1, 0, a), def(1, 1, b), def(2, 0, c), def(2, 1, a)]),
+        res[&BlockId(2)].before.defs
+    );
+    assert_eq!(
+        defs(&[def(1, 1, b), def(2, 0, c), def(2, 1, a)]),
+        res[&BlockId(2)].after.defs
+    );
+    assert_eq!(
+        defs(&[def(1, 1, b), def(2, 0, c), def(2, 1, a), def(3, 0, d)]),
+        res[&BlockId(3)].after.defs
+    );
+}
+
+#[test]
+fn chains_one_loop() {
+    let parsed = text::parse_statements(ONE_LOOP).unwrap();
+    let res = reaching::solve(&parsed.graph);
+
+    let [a, b, c, d] = [0, 1, 2, 3];
+
+    let ud = reaching::use_def(&parsed.graph, &res);
+    assert_eq!(3, ud.len());
+    assert_eq!(
+        vec![def(1, 0, a), def(2, 1, a)]
+            .into_iter()
+            .collect::<HashSet<_>>(),
+        ud[&use_(2, 0, a)]
+    );
+    assert_eq!(
+        vec![def(1, 1, b)].into_iter().collect::<HashSet<_>>(),
+        ud[&use_(2, 1, b)]
+    );
+    assert_eq!(
+        vec![def(2, 1, a)].into_iter().collect::<HashSet<_>>(),
+        ud[&use_(3, 0, a)]
+    );
+
+    let du = reaching::def_use(&parsed.graph, &res);
+    assert_eq!(5, du.len());
+    assert_eq!(
+        vec![use_(2, 0, a), use_(3, 0, a)]
+            .into_iter()
+            .collect::<HashSet<_>>(),
+        du[&def(2, 1, a)]
+    );
+    assert!(du[&def(2, 0, c)].is_empty());
+    assert!(du[&def(3, 0, d)].is_empty());
+}
@@ -0,0 +1,116 @@
+//! Reduced products, where a sign and an interval tighten each other
+
+use std::cmp::Ordering;
+
+use creek::cfg::{Block, BlockId};
+use creek::lattice::{Bound, Bounded, Interval, Lattice, Reduced, Sign, Widen};
+use creek::{text, Analyzer};
+
+/// Rules out the parts of the interval with the wrong sign, and finds the sign
+/// of intervals which don't cross zero
+fn tighten(range: &mut Interval, sign: &mut Sign) {
+    *range = match sign {
+        Sign::Bottom => Interval::Empty,
+        Sign::Neg => range.restrict(Ordering::Less, 0),
+        Sign::Zero => range.restrict(Ordering::Equal, 0),
+        Sign::Pos => range.restrict(Ordering::Greater, 0),
+        Sign::Top => *range,
+    };
+
+    let range_sign = match range {
+        Interval::Empty => Sign::Bottom,
+        Interval::Range(r) if r.lo() > Bound::Int(0) => Sign::Pos,
+        Interval::Range(r) if r.hi() < Bound::Int(0) => Sign::Neg,
+        _ if *range == Interval::constant(0) => Sign::Zero,
+        _ => Sign::Top,
+    };
+
+    *sign = sign.meet(&range_sign);
+}
+
+type Value = Reduced<Interval, Sign>;
+
+fn value(range: Interval, sign: Sign) -> Value {
+    Value::new(range, sign, tighten)
+}
+
+#[test]
+fn reduce() {
+    let pos = value(Interval::new(-5, 5), Sign::Pos);
+    assert_eq!(pos.first(), &Interval::new(1, 5));
+
+    let pos = value(Interval::new(3, Bound::PosInf), Sign::Top);
+    assert_eq!(pos.second(), &Sign::Pos);
+
+    // Joining loses the sign, but keeps the interval
+    let neg = value(Interval::new(-3, -1), Sign::Top);
+    let joined = pos.join(&neg);
+    assert_eq!(
+        joined.into_parts(),
+        (Interval::new(-3, Bound::PosInf), Sign::Top)
+    );
+
+    // Contradictions are found, and become bottom
+    let contradiction = value(Interval::new(-3, -1), Sign::Pos);
+    assert_eq!(contradiction, Value::bottom());
+
+    // `map` reduces again after changing the facts
+    let small = value(Interval::new(1, 5), Sign::Top);
+    let shifted = small.map(|range, _| (range - Interval::constant(10), Sign::Top));
+    assert_eq!(shifted.into_parts(), (Interval::new(-9, -5), Sign::Neg));
+}
+
+/// `reduce` can be a closure with its own state, which is kept by every fact
+/// made from the first one
+#[test]
+fn closure() {
+    let limit = Interval::new(-10, 10);
+    let clamp = move |range: &mut Interval, _: &mut Sign| *range = range.meet(&limit);
+
+    let a = Reduced::new(Interval::new(-20, 0), Sign::Top, clamp);
+    assert_eq!(a.first(), &Interval::new(-10, 0));
+    assert_eq!(
+        a.clone()
+            .map(|range, sign| (range * Interval::constant(5), sign))
+            .first(),
+        &Interval::new(-10, 0)
+    );
+
+    // Bottom and top have no `reduce` of their own, and pick it up when
+    // combined with a fact which does
+    let b = Reduced::bottom().join(&Reduced::new(Interval::new(5, 50), Sign::Pos, clamp));
+    assert_eq!(b.first(), &Interval::new(5, 10));
+    assert_eq!(a.join(&b).first(), &limit);
+
+    let wide = Reduced::top().meet(&b);
+    assert_eq!(wide.into_parts(), (Interval::new(5, 10), Sign::Pos));
+}
+
+/// ```plain
+/// +-1------------+    +-2------------+    +-3----------+
+/// | x = -5 .. 5  |--->| assume x > 0 |--->| x = x * -1 |
+/// +--------------+    +--------------+    +------------+
+/// ```
+#[test]
+fn analysis() {
+    let graph = text::parse("1 -> 2 : x = -5 .. 5\n2 -> 3 : assume x > 0\n3 : x = x * -1").unwrap();
+
+    // `map` reduces with the `tighten` of the entry fact, which reaches every
+    // fact through `join`
+    let trans = |block: &Block<String>, x: Value| match block.data.as_str() {
+        "x = -5 .. 5" => x.map(|_, _| (Interval::new(-5, 5), Sign::Top)),
+        "assume x > 0" => x.map(|range, sign| (range, sign.meet(&Sign::Pos))),
+        "x = x * -1" => x.map(|range, sign| (range * Interval::constant(-1), sign * Sign::Neg)),
+        _ => x,
+    };
+
+    let res = Analyzer::new_forward_lattice(trans)
+        .with_entry_fact(value(Interval::full(), Sign::Top))
+        .with_widening(Value::widen)
+        .solve(&graph);
+
+    let after = |id| res[&BlockId(id)].after.clone().into_parts();
+    assert_eq!(after(1), (Interval::new(-5, 5), Sign::Top));
+    assert_eq!(after(2), (Interval::new(1, 5), Sign::Pos));
+    assert_eq!(after(3), (Interval::new(-5, -1), Sign::Neg));
+}
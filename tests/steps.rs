@@ -0,0 +1,145 @@
+//! Stepping through a solve should visit nodes in the same order as `solve`
+//! and end up at the same fixpoint.
+
+mod cfg;
+
+use cfg::*;
+use creek::{Analyzer, Fact};
+use fnv::FnvHashSet;
+
+#[derive(Clone, Debug, PartialEq)]
+struct LivenessFact {
+    live: FnvHashSet<Variable>,
+}
+
+impl Fact for LivenessFact {}
+
+fn trans(block: &Block, mut fact: LivenessFact) -> LivenessFact {
+    for stmt in block.stmts.iter().rev() {
+        match stmt {
+            Statement::Declare(_) => {}
+            Statement::ConstAssign(var, _) => {
+                fact.live.remove(var);
+            }
+            Statement::VarAssign(var, war) => {
+                fact.live.remove(var);
+                fact.live.insert(*war);
+            }
+            Statement::ExprAssign(var, expr) => {
+                fact.live.remove(var);
+                fact.live.extend(expr.args.iter().copied());
+            }
+        }
+    }
+
+    fact
+}
+
+fn join(facts: Vec<LivenessFact>) -> LivenessFact {
+    let mut live = FnvHashSet::default();
+
+    for fact in facts {
+        live.extend(fact.live);
+    }
+
+    LivenessFact { live }
+}
+
+/// ```plain
+/// +-1-----+
+/// | a = 0 |
+/// +-------+
+///     |
+///     v
+/// +-2-----+
+/// | b = 1 |<-+
+/// | c = a |  |
+/// +-------+  |
+///   |   |    |
+///   |   +----+
+///   v
+/// +-3-----+
+/// | d = a |
+/// | e = b |
+/// +-------+
+/// ```
+fn one_loop() -> NodeGraph {
+    let mut graph = NodeGraph::new(block! {
+        1;
+        from => ;
+        to => 2;
+        (0 = 0)
+    });
+
+    graph.insert(block! {
+        2;
+        from => 1, 2;
+        to => 2, 3;
+        (1 = 1);
+        (2 = var 0)
+    });
+
+    graph.insert_exit(block! {
+        3;
+        from => 2;
+        to => ;
+        (3 = var 0);
+        (4 = var 1)
+    });
+
+    graph
+}
+
+fn top() -> LivenessFact {
+    LivenessFact {
+        live: FnvHashSet::default(),
+    }
+}
+
+#[test]
+fn steps_reach_fixpoint() {
+    let graph = one_loop();
+
+    let mut analyzer = Analyzer::new_backward(top(), trans, join);
+    let expected = analyzer.solve(&graph);
+
+    let mut steps = analyzer.solve_steps(&graph);
+    let mut visited = Vec::new();
+
+    while let Some(step) = steps.next() {
+        // The worklist never holds a node twice
+        let unique: FnvHashSet<_> = step.worklist.iter().collect();
+        assert_eq!(unique.len(), step.worklist.len());
+
+        // The yielded facts are the ones stored for the node
+        let info = &steps.infos()[&step.id];
+        assert_eq!(info.after, step.joined);
+        assert_eq!(info.before, step.transd);
+
+        visited.push(step.id);
+    }
+
+    assert_eq!(visited.len(), steps.stats().total_visits());
+    assert_eq!(expected, steps.finish());
+}
+
+#[test]
+fn finish_after_pausing() {
+    let graph = one_loop();
+
+    let mut analyzer = Analyzer::new_backward(top(), trans, join);
+    let expected = analyzer.solve(&graph);
+
+    let mut steps = analyzer.solve_steps(&graph);
+    let first = steps.next().unwrap();
+
+    // Block 3 is the exit, so it is visited first in a backwards problem
+    assert_eq!(BlockId(3), first.id);
+    assert!(first.changed);
+    assert_eq!(
+        first.worklist,
+        steps.worklist().copied().collect::<Vec<_>>()
+    );
+
+    assert_eq!(expected, steps.finish());
+}
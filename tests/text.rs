@@ -0,0 +1,139 @@
+//! Graphs written in the text format should parse into the same graph as one
+//! built by hand, and errors should point at the offending token.
+
+use creek::cfg::{BinOp, Block, BlockId, Expr, NodeGraph, Statement, Variable};
+use creek::text::{self, ParseErrorKind};
+use creek::Graph;
+
+/// ```plain
+/// +-1-----+
+/// | a = 0 |
+/// +-------+
+///     |
+///     v
+/// +-2-----+
+/// | b = a |<-+
+/// +-------+  |
+///   |   |    |
+///   |   +----+
+///   v
+/// +-3-----+
+/// +-------+
+/// ```
+const ONE_LOOP: &str = "
+# a = 0; loop { b = a }
+1 entry -> 2    : a = 0
+2 -> 2, 3       : b = var a
+3 exit
+";
+
+#[test]
+fn parse_one_loop() {
+    let graph = text::parse(ONE_LOOP).unwrap();
+
+    let mut expected = NodeGraph::new(Block::new(BlockId(1), "a = 0".to_string()));
+    expected.insert(Block::new(BlockId(2), "b = var a".to_string()));
+    expected.insert_exit(Block::new(BlockId(3), String::new()));
+    expected.add_edge(BlockId(1), BlockId(2));
+    expected.add_edge(BlockId(2), BlockId(2));
+    expected.add_edge(BlockId(2), BlockId(3));
+
+    assert_eq!(expected.get_entry(), graph.get_entry());
+    assert_eq!(expected.get_exit(), graph.get_exit());
+    assert_eq!(
+        expected.blocks().collect::<Vec<_>>(),
+        graph.blocks().collect::<Vec<_>>()
+    );
+
+    // Writing the graph back out gives something which parses the same
+    let again = text::parse(&text::write(&graph)).unwrap();
+    assert_eq!(
+        graph.blocks().collect::<Vec<_>>(),
+        again.blocks().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn errors() {
+    let err = |src| text::parse(src).unwrap_err();
+
+    let e = err("1 -> 2\n2 -> 3 : x");
+    assert_eq!((2, 6), (e.line, e.column));
+    assert_eq!(ParseErrorKind::UnknownBlock(BlockId(3)), e.kind);
+    assert_eq!("2:6: block 3 is never defined", e.to_string());
+
+    let e = err("1 entry\n  1 exit");
+    assert_eq!((2, 3), (e.line, e.column));
+    assert_eq!(ParseErrorKind::DuplicateBlock(BlockId(1)), e.kind);
+
+    let e = err("1 entry\n2 entry");
+    assert_eq!((2, 3), (e.line, e.column));
+    assert_eq!(ParseErrorKind::DuplicateEntry, e.kind);
+
+    let e = err("1 -> , 2");
+    assert_eq!((1, 6), (e.line, e.column));
+    assert_eq!(ParseErrorKind::ExpectedId, e.kind);
+
+    let e = err("1 exits");
+    assert_eq!((1, 3), (e.line, e.column));
+    assert_eq!(ParseErrorKind::Unexpected('e'), e.kind);
+
+    let e = err("# nothing\n");
+    assert_eq!(ParseErrorKind::Empty, e.kind);
+}
+
+#[test]
+fn parse_statements() {
+    let src = "
+1 -> 2 : var x; a = 5
+2      : x = a; b = -1;
+";
+
+    let parsed = text::parse_statements(src).unwrap();
+    let [x, a, b] = [Variable(0), Variable(1), Variable(2)];
+
+    assert_eq!(vec!["x", "a", "b"], parsed.names);
+    assert_eq!(
+        vec![
+            vec![Statement::Declare(x), Statement::ConstAssign(a, 5)],
+            vec![Statement::VarAssign(x, a), Statement::ConstAssign(b, -1)],
+        ],
+        parsed
+            .graph
+            .blocks()
+            .map(|b| b.data.clone())
+            .collect::<Vec<_>>()
+    );
+
+    let e = text::parse_statements("1 : x = 5;  var = y").unwrap_err();
+    assert_eq!((1, 13), (e.line, e.column));
+
+    let e = text::parse_statements("1 -> 1 :  x = 99999999999").unwrap_err();
+    assert_eq!((1, 15), (e.line, e.column));
+    assert_eq!(
+        ParseErrorKind::Payload("99999999999 is not a valid constant".to_string()),
+        e.kind
+    );
+}
+
+#[test]
+fn parse_expressions() {
+    let parsed = text::parse_statements("1 : c = a + b; d = a*b; e = c - -1").unwrap_err();
+    assert_eq!((1, 25), (parsed.line, parsed.column));
+
+    let parsed = text::parse_statements("1 : c = a + b; d = a*b; e = c-d").unwrap();
+    let [c, a, b, d, e] = [0, 1, 2, 3, 4].map(Variable);
+    let expr = |op, lhs, rhs| Expr {
+        op,
+        args: [lhs, rhs],
+    };
+
+    assert_eq!(
+        vec![
+            Statement::ExprAssign(c, expr(BinOp::Add, a, b)),
+            Statement::ExprAssign(d, expr(BinOp::Mul, a, b)),
+            Statement::ExprAssign(e, expr(BinOp::Sub, c, d)),
+        ],
+        parsed.graph.blocks().next().unwrap().data
+    );
+}
@@ -0,0 +1,79 @@
+//! Solving several problems in one pass gives the same facts as solving each
+//! of them on its own
+
+use creek::analyses::{available, busy, constants, live, reaching};
+use creek::cfg::{Block, BlockId};
+use creek::lattice::{Bound, Interval, PowerSet, Widen};
+use creek::{solve_together, text, Analyzer, HashSet};
+
+const LOOP: &str = "
+1 -> 2       : a = 1; b = 2; c = a + b
+2 -> 3, 4    : d = a + b; b = c
+3 -> 2       : a = a * b; e = 7
+4            : f = a + b; g = e
+";
+
+#[test]
+fn forward() {
+    let graph = text::parse_statements(LOOP).unwrap();
+    let graph = &graph.graph;
+
+    let mut reaching = Analyzer::new_forward(
+        reaching::ReachingDefs::new(HashSet::default()),
+        reaching::trans,
+        reaching::join,
+    );
+    let mut available = Analyzer::new_forward(PowerSet::all(), available::trans, available::join)
+        .with_entry_fact(PowerSet::empty());
+    let mut constants = Analyzer::new_forward_lattice(constants::trans);
+
+    let (defs, exprs, consts) =
+        solve_together(graph, (&mut reaching, &mut available, &mut constants));
+
+    assert_eq!(defs, reaching::solve(graph));
+    assert_eq!(exprs, available::solve(graph));
+    assert_eq!(consts, constants::solve(graph));
+
+    // The analyzers can still be used on their own afterwards
+    assert_eq!(reaching.solve(graph), defs);
+}
+
+#[test]
+fn backward() {
+    let graph = text::parse_statements(LOOP).unwrap();
+    let graph = &graph.graph;
+
+    let mut live = Analyzer::new_backward(
+        live::LiveVars::new(HashSet::default()),
+        live::trans,
+        live::join,
+    );
+    let mut busy = Analyzer::new_backward(PowerSet::all(), busy::trans, busy::join)
+        .with_exit_fact(PowerSet::empty());
+
+    let (vars, exprs) = solve_together(graph, (&mut live, &mut busy));
+
+    assert_eq!(vars, live::solve(graph));
+    assert_eq!(exprs, busy::solve(graph));
+}
+
+/// Problems which need widening still stabilize when solved together
+#[test]
+fn widening() {
+    let graph = text::parse("1 -> 2 : i = 0\n2 -> 2, 3 : i += 1\n3").unwrap();
+
+    let trans = |block: &Block<String>, i: Interval| match block.data.as_str() {
+        "i = 0" => Interval::constant(0),
+        _ => i + Interval::constant(1),
+    };
+    let negated = |block: &Block<String>, i: Interval| -trans(block, -i);
+
+    let mut up = Analyzer::new_forward_lattice(trans).with_widening(Interval::widen);
+    let mut down = Analyzer::new_forward_lattice(negated).with_widening(Interval::widen);
+
+    let (up, down) = solve_together(&graph, (&mut up, &mut down));
+    let exit = BlockId(3);
+
+    assert_eq!(up[&exit].before, Interval::new(1, Bound::PosInf));
+    assert_eq!(down[&exit].before, -Interval::new(1, Bound::PosInf));
+}